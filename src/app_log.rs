@@ -0,0 +1,26 @@
+use std::sync::Mutex;
+
+// Entries beyond this are dropped oldest-first
+const MAX_ENTRIES: usize = 500;
+
+// Messages for the log panel; loaders run without access to the viewer, so they go through here
+static ENTRIES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub fn log(message: String) {
+    let entry = format!("{}  {}", chrono::Local::now().format("%H:%M:%S"), message);
+    if let Ok(mut entries) = ENTRIES.lock() {
+        entries.push(entry);
+        let excess = entries.len().saturating_sub(MAX_ENTRIES);
+        entries.drain(..excess);
+    }
+}
+
+pub fn entries() -> Vec<String> {
+    ENTRIES.lock().map(|entries| entries.clone()).unwrap_or_default()
+}
+
+pub fn clear() {
+    if let Ok(mut entries) = ENTRIES.lock() {
+        entries.clear();
+    }
+}
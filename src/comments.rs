@@ -0,0 +1,47 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+// Oldest comments are dropped from the file beyond this many
+pub const MAX_COMMENTS: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Comment {
+    user: String,
+    message: String,
+}
+
+// (username, message) pairs from the shared comment file, oldest first
+pub fn read_comments<P: AsRef<Path>>(path: P) -> Result<VecDeque<(String, String)>, Box<dyn Error>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        // Nobody has written to a new feed yet
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(VecDeque::new()),
+        Err(err) => return Err(err.into()),
+    };
+    if content.trim().is_empty() {
+        return Ok(VecDeque::new());
+    }
+    let comments: Vec<Comment> = serde_json::from_str(&content)?;
+    Ok(comments.into_iter().map(|comment| (comment.user, comment.message)).collect())
+}
+
+// Re-read the file right before writing so comments posted by other viewers are kept
+pub fn append_comment<P: AsRef<Path>>(path: P, user: &str, message: &str) -> Result<VecDeque<(String, String)>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut comments = read_comments(path)?;
+    comments.push_back((user.to_string(), message.to_string()));
+    while comments.len() > MAX_COMMENTS {
+        comments.pop_front();
+    }
+    
+    let entries: Vec<Comment> = comments.iter()
+        .map(|(user, message)| Comment { user: user.clone(), message: message.clone() })
+        .collect();
+    // Write beside the target and rename, so readers never see a half-written file
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, serde_json::to_string_pretty(&entries)?)?;
+    std::fs::rename(&temp_path, path)?;
+    Ok(comments)
+}
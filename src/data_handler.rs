@@ -1,31 +1,32 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::error::Error;
 use std::fs::File;
-use csv::ReaderBuilder;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use csv::{ReaderBuilder, WriterBuilder};
 use reqwest::blocking::Client;
-use serde_json::Value;
-use crate::data_types::TableData;
+use reqwest::header::{CACHE_CONTROL, ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use crate::app_log;
+use crate::data_types::{ColumnType, CsvOptions, DataSource, ExportFormat, FetchResult, HeaderConfig, LoadOptions, MergeStrategy, TableData};
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
 
 // Common header processing logic used by both local CSV and Google Sheets
-fn process_headers(headers: Vec<String>) -> (Vec<String>, Vec<bool>) {
-    let columns_to_hide = vec![
-        "sport_id", "team_members", "team_name",
-        "info", "result_code", "position_pre"
-    ];
-    
+fn process_headers(headers: Vec<String>, config: &HeaderConfig) -> (Vec<String>, Vec<bool>) {
     let mut processed_headers = Vec::new();
     let mut visible_columns = Vec::new();
     
     for header in headers {
-        // Check if this column should be hidden
-        let should_hide = columns_to_hide.iter()
-            .any(|col| header.to_lowercase().contains(col));
+        let should_hide = is_column_hidden(&header, config);
         
         visible_columns.push(!should_hide);
         
         if !should_hide {
             // Apply header replacements
-            let processed_header = replace_header(&header);
+            let processed_header = replace_header(&header, config);
             processed_headers.push(processed_header);
         }
     }
@@ -33,34 +34,30 @@ fn process_headers(headers: Vec<String>) -> (Vec<String>, Vec<bool>) {
     (processed_headers, visible_columns)
 }
 
-fn replace_header(header: &str) -> String {
+// Whether a raw header is left out when loading
+pub fn is_column_hidden(header: &str, config: &HeaderConfig) -> bool {
+    if let Some(&shown) = config.column_overrides.get(header) {
+        return !shown;
+    }
+    let header = header.to_lowercase();
+    config.hidden_columns.iter().any(|col| header.contains(col.as_str()))
+}
+
+pub fn replace_header(header: &str, config: &HeaderConfig) -> String {
     let header_lower = header.to_lowercase();
     
-    // Header replacements mapping
-    let replacements = [
-        ("category", "Series"),
-        ("first_name", "Name"),
-        ("last_name", "Surname"),
-        ("organization", "Club"),
-        ("napat", "X"),
-        ("result", "Result"),
-        ("posit.", "Rank")
-    ];
-    
-    // First check for part-X and psum-X patterns
-    if header_lower.contains("part-") {
-        if let Some(part_num) = header.split('-').nth(1) {
-            return format!("S{}", part_num);
-        }
-    } else if header_lower.contains("psum-") {
-        if let Some(part_num) = header.split('-').nth(1) {
-            return format!("P{}", part_num);
+    // First check for numbered patterns like part-X and psum-X
+    for (pattern, prefix) in config.numbered_prefixes.iter() {
+        if header_lower.contains(pattern.as_str()) {
+            if let Some(part_num) = header.split('-').nth(1) {
+                return format!("{}{}", prefix, part_num);
+            }
         }
     }
     
     // Then check other replacements
-    for (original, replacement) in replacements.iter() {
-        if header_lower.contains(original) {
+    for (original, replacement) in config.replacements.iter() {
+        if header_lower.contains(original.as_str()) {
             return replacement.to_string();
         }
     }
@@ -68,18 +65,122 @@ fn replace_header(header: &str) -> String {
     header.to_string()
 }
 
-// Load data from local CSV file
-pub fn load_csv_file<P: AsRef<Path>>(path: P) -> Result<TableData, Box<dyn Error>> {
-    let mut data = TableData::empty();
+// Load data from local CSV file, or from standard input when the path is "-"
+pub fn load_csv_file<P: AsRef<Path>>(
+    path: P,
+    options: &CsvOptions,
+    header_config: &HeaderConfig,
+) -> Result<TableData, Box<dyn Error>> {
+    if path.as_ref() == Path::new("-") {
+        // Stdin can only be read once, so buffer it to detect the delimiter first
+        let mut content = String::new();
+        std::io::stdin().lock().read_to_string(&mut content)?;
+        let delimiter = detect_delimiter_in(&content, options);
+        return load_csv_from_reader(skip_bom(content.as_bytes())?, delimiter, options, header_config);
+    }
     
     // Detect delimiter
-    let delimiter = detect_delimiter(&path)?;
+    let delimiter = detect_delimiter(&path, options)?;
     
     let file = File::open(&path)?;
+    load_csv_from_reader(skip_bom(file)?, delimiter, options, header_config)
+}
+
+// Fetch delimited text over HTTP(S) and parse it like a local file
+pub fn load_csv_url(
+    url: &str,
+    options: &CsvOptions,
+    header_config: &HeaderConfig,
+) -> Result<TableData, Box<dyn Error>> {
+    let body = Client::new()
+        .get(url)
+        .send()?
+        .error_for_status()?
+        .bytes()?;
+    let delimiter = detect_delimiter_in(&String::from_utf8_lossy(&body), options);
+    load_csv_from_reader(skip_bom(body.as_ref())?, delimiter, options, header_config)
+}
+
+pub fn is_xlsx_path<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().extension().is_some_and(|ext| ext.eq_ignore_ascii_case("xlsx"))
+}
+
+#[cfg(feature = "xlsx")]
+pub fn xlsx_sheet_names<P: AsRef<Path>>(path: P) -> Result<Vec<String>, Box<dyn Error>> {
+    let workbook: calamine::Xlsx<_> = calamine::open_workbook(path)?;
+    Ok(calamine::Reader::sheet_names(&workbook).to_owned())
+}
+
+#[cfg(not(feature = "xlsx"))]
+pub fn xlsx_sheet_names<P: AsRef<Path>>(_path: P) -> Result<Vec<String>, Box<dyn Error>> {
+    Err("This build was compiled without Excel support".into())
+}
+
+// One sheet of an Excel workbook; the first non-empty row holds the headers
+#[cfg(feature = "xlsx")]
+pub fn load_xlsx_file<P: AsRef<Path>>(
+    path: P,
+    sheet_index: usize,
+    header_config: &HeaderConfig,
+) -> Result<TableData, Box<dyn Error>> {
+    use calamine::Reader;
+    
+    let mut workbook: calamine::Xlsx<_> = calamine::open_workbook(path)?;
+    let range = workbook.worksheet_range_at(sheet_index)
+        .ok_or_else(|| format!("The workbook has no sheet {}", sheet_index + 1))??;
+    let mut rows = range.rows()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect::<Vec<String>>())
+        .filter(|row| row.iter().any(|cell| !cell.trim().is_empty()));
+    
+    let mut data = TableData::empty();
+    let Some(headers) = rows.next() else {
+        return Ok(data);
+    };
+    data.raw_headers = headers.clone();
+    let (processed_headers, visible_columns) = process_headers(headers, header_config);
+    data.headers = processed_headers;
+    data.rows = rows
+        .map(|row| row.into_iter()
+            .zip(&visible_columns)
+            .filter(|(_, visible)| **visible)
+            .map(|(cell, _)| cell)
+            .collect())
+        .collect();
+    Ok(data)
+}
+
+#[cfg(not(feature = "xlsx"))]
+pub fn load_xlsx_file<P: AsRef<Path>>(
+    _path: P,
+    _sheet_index: usize,
+    _header_config: &HeaderConfig,
+) -> Result<TableData, Box<dyn Error>> {
+    Err("This build was compiled without Excel support".into())
+}
+
+// Consume a leading UTF-8 byte-order mark, which the csv reader would otherwise keep in the first header
+fn skip_bom<R: Read>(input: R) -> std::io::Result<BufReader<R>> {
+    let mut reader = BufReader::new(input);
+    if reader.fill_buf()?.starts_with(UTF8_BOM) {
+        reader.consume(UTF8_BOM.len());
+    }
+    Ok(reader)
+}
+
+fn load_csv_from_reader<R: Read>(
+    input: R,
+    delimiter: char,
+    options: &CsvOptions,
+    header_config: &HeaderConfig,
+) -> Result<TableData, Box<dyn Error>> {
+    let mut data = TableData::empty();
+    data.delimiter = delimiter as u8;
+    
     let mut reader = ReaderBuilder::new()
         .delimiter(delimiter as u8)
         .flexible(true)
-        .from_reader(file);
+        .comment(options.comment_char)
+        .from_reader(input);
     
     // Process headers
     let headers: Vec<String> = reader.headers()?
@@ -87,7 +188,8 @@ pub fn load_csv_file<P: AsRef<Path>>(path: P) -> Result<TableData, Box<dyn Error
         .map(String::from)
         .collect();
     
-    let (processed_headers, visible_columns) = process_headers(headers);
+    data.raw_headers = headers.clone();
+    let (processed_headers, visible_columns) = process_headers(headers, header_config);
     data.headers = processed_headers;
     
     // Process rows
@@ -112,16 +214,45 @@ pub fn load_csv_file<P: AsRef<Path>>(path: P) -> Result<TableData, Box<dyn Error
     Ok(data)
 }
 
-fn detect_delimiter<P: AsRef<Path>>(path: P) -> Result<char, Box<dyn Error>> {
-    let mut file = File::open(path)?;
-    let mut first_line = String::new();
-    std::io::Read::read_to_string(&mut file, &mut first_line)?;
+fn detect_delimiter<P: AsRef<Path>>(path: P, options: &CsvOptions) -> Result<char, Box<dyn Error>> {
+    let mut content = String::new();
+    File::open(path)?.read_to_string(&mut content)?;
     
-    if first_line.contains(';') {
-        Ok(';')
-    } else {
-        Ok(',')
-    }
+    Ok(detect_delimiter_in(&content, options))
+}
+
+// The most frequent of tab, pipe, semicolon and comma in the first line that is neither blank
+// nor a comment; comma when none appear
+fn detect_delimiter_in(text: &str, options: &CsvOptions) -> char {
+    let first_line = text.lines()
+        .map(|line| line.trim_start_matches('\u{feff}'))
+        .find(|line| {
+            !line.trim().is_empty()
+                && options.comment_char.is_none_or(|comment| line.as_bytes().first() != Some(&comment))
+        })
+        .unwrap_or("");
+    // max_by_key keeps the last of equal counts, so ties go to whichever comes later in this
+    // list: a semicolon file with a comma in its header still splits on semicolons
+    [',', ';', '|', '\t']
+        .into_iter()
+        .map(|delimiter| (first_line.matches(delimiter).count(), delimiter))
+        .filter(|&(count, _)| count > 0)
+        .max_by_key(|&(count, _)| count)
+        .map_or(',', |(_, delimiter)| delimiter)
+}
+
+// Read a list of Google Sheets, one "url" or "url|SheetName" per non-empty line
+pub fn read_url_list<P: AsRef<Path>>(path: P) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let content = std::fs::read_to_string(path)?;
+    let sheets = content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.split_once('|') {
+            Some((url, sheet)) => (url.trim().to_string(), sheet.trim().to_string()),
+            None => (line.to_string(), String::new()),
+        })
+        .collect();
+    Ok(sheets)
 }
 
 // Extract spreadsheet ID from URL
@@ -137,15 +268,75 @@ fn extract_spreadsheet_id(url: &str) -> Result<String, Box<dyn Error>> {
     Err("Invalid spreadsheet URL".into())
 }
 
-// Load data from Google Sheets using public sheets API (no OAuth needed)
-pub fn load_google_sheet(url: &str, sheet_name: &str) -> Result<TableData, Box<dyn Error>> {
-    let mut data = TableData::empty();
-    
-    // Get spreadsheet ID from URL
-    let spreadsheet_id = extract_spreadsheet_id(url)?;
-    
-    // Default to "Sheet1" if no sheet name provided
-    let sheet = if sheet_name.is_empty() { "Sheet1" } else { sheet_name };
+fn cache_path(spreadsheet_id: &str, sheet: &str) -> PathBuf {
+    let file_name: String = format!("{}_{}.json", spreadsheet_id, sheet)
+        .chars()
+        .map(|c| if c.is_alphanumeric() || "-_.".contains(c) { c } else { '_' })
+        .collect();
+    std::env::temp_dir().join("simple-viewer-cache").join(file_name)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+// Cached API response, if one exists and hasn't expired
+fn read_cached_response(path: &Path) -> Option<Value> {
+    let mut cached: Value = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    let expires_at = cached.get("expires_at")?.as_u64()?;
+    if unix_now() >= expires_at {
+        return None;
+    }
+    Some(cached.get_mut("response")?.take())
+}
+
+// How long a response may be reused: `ttl`, or less if the server's max-age is shorter.
+// Responses marked no-store are never written to disk.
+fn cache_lifetime(cache_control: Option<&str>, ttl: Duration) -> Option<Duration> {
+    let Some(cache_control) = cache_control else {
+        return Some(ttl);
+    };
+    let mut max_age = None;
+    for directive in cache_control.split(',').map(str::trim) {
+        if directive.eq_ignore_ascii_case("no-store") {
+            return None;
+        }
+        if let Some(seconds) = directive.strip_prefix("max-age=") {
+            max_age = seconds.parse().ok().map(Duration::from_secs);
+        }
+    }
+    Some(max_age.map_or(ttl, |max_age| max_age.min(ttl)))
+}
+
+fn write_cached_response(path: &Path, response: &Value, lifetime: Duration) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let cached = json!({
+        "expires_at": unix_now() + lifetime.as_secs(),
+        "response": response,
+    });
+    std::fs::write(path, serde_json::to_string(&cached)?)?;
+    Ok(())
+}
+
+// Fetch the sheet's values, served from the on-disk cache while it is fresh.
+// A zero `cache_ttl` bypasses the cache. With an `etag` from an earlier fetch the request is
+// conditional: None means the sheet is unchanged, otherwise `etag` is updated from the response.
+fn fetch_sheet_values(
+    spreadsheet_id: &str,
+    sheet: &str,
+    cache_ttl: Duration,
+    etag: &mut Option<String>,
+) -> Result<Option<Value>, Box<dyn Error>> {
+    let cache_file = cache_path(spreadsheet_id, sheet);
+    if !cache_ttl.is_zero() {
+        if let Some(response) = read_cached_response(&cache_file) {
+            app_log::log(format!("Sheet cache hit: {}", sheet));
+            return Ok(Some(response));
+        }
+        app_log::log(format!("Sheet cache miss: {}", sheet));
+    }
     
     // Build Google Sheets API URL
     let api_url = format!(
@@ -155,18 +346,100 @@ pub fn load_google_sheet(url: &str, sheet_name: &str) -> Result<TableData, Box<d
     
     // Make API request
     let client = Client::new();
-    let response = client.get(&api_url).send()?;
+    let mut request = client.get(&api_url);
+    if let Some(etag) = etag.as_deref() {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    let response = request.send()?;
     
+    if response.status() == StatusCode::NOT_MODIFIED {
+        app_log::log(format!("Sheet not modified: {}", sheet));
+        return Ok(None);
+    }
     if !response.status().is_success() {
         return Err(format!("API error: {}", response.status()).into());
     }
     
+    let header = |name| response.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+    let cache_control = header(CACHE_CONTROL);
+    *etag = header(ETAG);
     let json: Value = response.json()?;
     
+    if !cache_ttl.is_zero() {
+        if let Some(lifetime) = cache_lifetime(cache_control.as_deref(), cache_ttl) {
+            if let Err(err) = write_cached_response(&cache_file, &json, lifetime) {
+                app_log::log(format!("Could not write sheet cache: {}", err));
+            }
+        }
+    }
+    Ok(Some(json))
+}
+
+// Create a spreadsheet owned by the token's account, fill its first sheet and return its URL
+pub fn create_google_sheet(
+    access_token: &str,
+    title: &str,
+    headers: &[String],
+    rows: &[Vec<String>],
+) -> Result<String, Box<dyn Error>> {
+    let client = Client::new();
+    let created: Value = client.post("https://sheets.googleapis.com/v4/spreadsheets")
+        .bearer_auth(access_token)
+        .json(&json!({ "properties": { "title": title } }))
+        .send()?
+        .error_for_status()?
+        .json()?;
+    let spreadsheet_id = created["spreadsheetId"].as_str().ok_or("No spreadsheet ID in the response")?;
+    let url = created["spreadsheetUrl"].as_str().ok_or("No spreadsheet URL in the response")?;
+    let sheet = created["sheets"][0]["properties"]["title"].as_str().unwrap_or("Sheet1");
+    
+    let values: Vec<&[String]> = std::iter::once(headers)
+        .chain(rows.iter().map(Vec::as_slice))
+        .collect();
+    let range = urlencoding::encode(&format!("'{}'!A1", sheet.replace('\'', "''"))).into_owned();
+    client.put(format!(
+            "https://sheets.googleapis.com/v4/spreadsheets/{}/values/{}?valueInputOption=RAW",
+            spreadsheet_id, range
+        ))
+        .bearer_auth(access_token)
+        .json(&json!({ "values": values }))
+        .send()?
+        .error_for_status()?;
+    Ok(url.to_string())
+}
+
+// Load data from Google Sheets using public sheets API (no OAuth needed).
+// Pass the ETag of the last fetch to skip the download when the sheet hasn't changed;
+// it is replaced with the new one when there is new data.
+pub fn load_google_sheet(
+    url: &str,
+    sheet_name: &str,
+    header_config: &HeaderConfig,
+    cache_ttl: Duration,
+    etag: &mut Option<String>,
+) -> Result<FetchResult, Box<dyn Error>> {
+    // Get spreadsheet ID from URL
+    let spreadsheet_id = extract_spreadsheet_id(url)?;
+    
+    // Default to "Sheet1" if no sheet name provided
+    let sheet = if sheet_name.is_empty() { "Sheet1" } else { sheet_name };
+    
+    match fetch_sheet_values(&spreadsheet_id, sheet, cache_ttl, etag)? {
+        Some(json) => Ok(FetchResult::Updated(parse_sheet_values(&json, header_config))),
+        None => Ok(FetchResult::Unchanged),
+    }
+}
+
+fn parse_sheet_values(json: &Value, header_config: &HeaderConfig) -> TableData {
+    let mut data = TableData::empty();
+    
     // Process the data
     if let Some(values) = json.get("values").and_then(Value::as_array) {
         if values.is_empty() {
-            return Ok(data);
+            return data;
         }
         
         // Find header row (look for "category")
@@ -183,7 +456,7 @@ pub fn load_google_sheet(url: &str, sheet_name: &str) -> Result<TableData, Box<d
         // Get relevant data starting from header row
         let relevant_data = &values[start_index..];
         if relevant_data.is_empty() {
-            return Ok(data);
+            return data;
         }
         
         // Process headers
@@ -195,7 +468,8 @@ pub fn load_google_sheet(url: &str, sheet_name: &str) -> Result<TableData, Box<d
                 .map(|v| v.as_str().unwrap_or("").to_string())
                 .collect();
             
-            let (processed_headers, visible_columns) = process_headers(headers);
+            data.raw_headers = headers.clone();
+            let (processed_headers, visible_columns) = process_headers(headers, header_config);
             data.headers = processed_headers;
             
             // Process data rows
@@ -227,5 +501,255 @@ pub fn load_google_sheet(url: &str, sheet_name: &str) -> Result<TableData, Box<d
         }
     }
     
-    Ok(data)
+    data
+}
+
+// Cheap check that a source can still be reached, without downloading it
+pub fn source_reachable(source: &DataSource) -> bool {
+    match source {
+        DataSource::Local(path) => path == Path::new("-") || path.exists(),
+        DataSource::Workbook(path, _) => path.exists(),
+        DataSource::Cloud(url, _) | DataSource::Url(url) => Client::builder()
+            .timeout(HEALTH_CHECK_TIMEOUT)
+            .build()
+            .and_then(|client| client.head(url).send())
+            .is_ok_and(|response| response.status().is_success()),
+        DataSource::Inline(_) => true,
+        DataSource::Merged { primary, secondary, .. } => source_reachable(primary) && source_reachable(secondary),
+    }
+}
+
+// Per column: true when every non-empty value is a number
+pub fn infer_column_types(data: &TableData) -> Vec<bool> {
+    (0..data.headers.len())
+        .map(|col| matches!(data.column_type(col), ColumnType::Integer | ColumnType::Float))
+        .collect()
+}
+
+// Load any kind of source; merged sources load both sides
+pub fn load_source(source: &DataSource, options: &LoadOptions) -> Result<TableData, Box<dyn Error>> {
+    match source {
+        DataSource::Local(path) => load_csv_file(path, &options.csv_options, &options.header_config),
+        DataSource::Cloud(url, sheet) => match load_google_sheet(url, sheet, &options.header_config, options.sheet_cache_ttl, &mut None)? {
+            FetchResult::Updated(data) => Ok(data),
+            // Only a request carrying an ETag can come back unchanged
+            FetchResult::Unchanged => Err("Sheet reported unchanged without an ETag".into()),
+        },
+        DataSource::Url(url) => load_csv_url(url, &options.csv_options, &options.header_config),
+        DataSource::Workbook(path, sheet_index) => load_xlsx_file(path, *sheet_index, &options.header_config),
+        DataSource::Inline(data) => Ok(data.clone()),
+        DataSource::Merged { primary, secondary, key_col, strategy } => {
+            let primary = load_source(primary, options)?;
+            let secondary = load_source(secondary, options)?;
+            merge_tables(primary, &secondary, key_col, *strategy)
+        }
+    }
+}
+
+// Overlay `secondary` onto `primary`, matching rows by the value in `key_col`
+pub fn merge_tables(
+    mut primary: TableData,
+    secondary: &TableData,
+    key_col: &str,
+    strategy: MergeStrategy,
+) -> Result<TableData, Box<dyn Error>> {
+    let primary_key = primary.headers.iter()
+        .position(|header| header == key_col)
+        .ok_or_else(|| format!("Key column '{}' not found in primary source", key_col))?;
+    let secondary_key = secondary.headers.iter()
+        .position(|header| header == key_col)
+        .ok_or_else(|| format!("Key column '{}' not found in secondary source", key_col))?;
+    
+    // Secondary rows are rearranged into the primary column order, matched by header name
+    let column_map: Vec<Option<usize>> = primary.headers.iter()
+        .map(|header| secondary.headers.iter().position(|h| h == header))
+        .collect();
+    
+    for secondary_row in &secondary.rows {
+        let key = secondary_row.get(secondary_key).map_or("", String::as_str);
+        let row: Vec<String> = column_map.iter()
+            .map(|col| col.and_then(|c| secondary_row.get(c)).cloned().unwrap_or_default())
+            .collect();
+        
+        let existing = primary.rows.iter()
+            .position(|r| r.get(primary_key).map(String::as_str) == Some(key));
+        match existing {
+            Some(row_idx) => primary.rows[row_idx] = row,
+            None if strategy == MergeStrategy::ReplaceAndAppend => primary.rows.push(row),
+            None => {}
+        }
+    }
+    
+    Ok(primary)
+}
+
+// One row per numeric column: column_name, count, min, max, mean, median, stddev
+pub fn write_column_statistics<W: Write>(output: W, data: &TableData) -> Result<(), Box<dyn Error>> {
+    let mut writer = WriterBuilder::new().from_writer(output);
+    writer.write_record(["column_name", "count", "min", "max", "mean", "median", "stddev"])?;
+    for (col, header) in data.headers.iter().enumerate() {
+        let Some(stats) = data.column_statistics(col) else {
+            continue;
+        };
+        writer.write_record([
+            header.clone(),
+            stats.count.to_string(),
+            stats.min.to_string(),
+            stats.max.to_string(),
+            stats.mean.to_string(),
+            stats.median.to_string(),
+            stats.stddev.to_string(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_csv<W: Write>(
+    output: W,
+    delimiter: u8,
+    headers: &[String],
+    rows: &[Vec<String>],
+) -> Result<(), Box<dyn Error>> {
+    let mut writer = WriterBuilder::new().delimiter(delimiter).flexible(true).from_writer(output);
+    writer.write_record(headers)?;
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// Write a header row plus `rows` in the given format; `delimiter` only applies to CSV
+pub fn write_table<W: Write>(
+    output: W,
+    format: ExportFormat,
+    delimiter: u8,
+    headers: &[String],
+    rows: &[Vec<String>],
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        ExportFormat::Csv => write_csv(output, delimiter, headers, rows)?,
+        ExportFormat::Json => {
+            // One object per row, keyed by header
+            let records: Vec<serde_json::Map<String, Value>> = rows.iter()
+                .map(|row| headers.iter()
+                    .cloned()
+                    .zip(row.iter().map(|cell| Value::String(cell.clone())))
+                    .collect())
+                .collect();
+            serde_json::to_writer_pretty(output, &records)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+    
+    #[test]
+    fn detects_tab_delimiter() {
+        assert_eq!(detect_delimiter_in("Name\tSurname\tClub\tResult\nAnna\tVirtanen\tHKV\t98.5\n", &CsvOptions::default()), '\t');
+    }
+    
+    #[test]
+    fn detects_pipe_delimiter() {
+        assert_eq!(detect_delimiter_in("Name|Surname|Club|Result\nAnna|Virtanen|HKV|98,5\n", &CsvOptions::default()), '|');
+    }
+    
+    #[test]
+    fn detects_semicolon_delimiter() {
+        assert_eq!(detect_delimiter_in("Name;Surname;Club;Result\nAnna;Virtanen;HKV;98,5\n", &CsvOptions::default()), ';');
+        assert_eq!(detect_delimiter_in("Name;Points, total\n", &CsvOptions::default()), ';');
+    }
+    
+    #[test]
+    fn skips_comment_and_blank_lines_before_detecting() {
+        let text = "# Generated by timing system, v2\n\nName;Surname;Result\nAnna;Virtanen;98,5\n";
+        assert_eq!(detect_delimiter_in(text, &CsvOptions::default()), ';');
+        assert_eq!(detect_delimiter_in(text, &CsvOptions { comment_char: None }), ',');
+    }
+    
+    #[test]
+    fn falls_back_to_comma() {
+        assert_eq!(detect_delimiter_in("Name,Surname\n", &CsvOptions::default()), ',');
+        assert_eq!(detect_delimiter_in("Name\n", &CsvOptions::default()), ',');
+    }
+    
+    #[test]
+    fn cache_lifetime_is_capped_by_ttl() {
+        let ttl = Duration::from_secs(30);
+        assert_eq!(cache_lifetime(None, ttl), Some(ttl));
+        assert_eq!(cache_lifetime(Some("private"), ttl), Some(ttl));
+        assert_eq!(cache_lifetime(Some("private, max-age=10"), ttl), Some(Duration::from_secs(10)));
+        assert_eq!(cache_lifetime(Some("max-age=300"), ttl), Some(ttl));
+        assert_eq!(cache_lifetime(Some("no-store, max-age=300"), ttl), None);
+    }
+    
+    #[test]
+    fn strips_utf8_bom_from_first_header() {
+        const FIXTURE: &[u8] = b"\xEF\xBB\xBFcategory,Name,Result\nU16,Anna,98.5\n";
+        let header_config = HeaderConfig {
+            hidden_columns: vec!["category".to_string()],
+            numbered_prefixes: Vec::new(),
+            replacements: Vec::new(),
+            column_overrides: HashMap::new(),
+        };
+        let input = skip_bom(FIXTURE).unwrap();
+        let data = load_csv_from_reader(input, ',', &CsvOptions::default(), &header_config).unwrap();
+        
+        assert_eq!(data.raw_headers[0], "category");
+        assert_eq!(data.headers, ["Name", "Result"]);
+        assert_eq!(data.rows, [["Anna", "98.5"]]);
+    }
+    
+    fn table(headers: &[&str], rows: &[&[&str]]) -> TableData {
+        TableData {
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            rows: rows.iter().map(|row| row.iter().map(|cell| cell.to_string()).collect()).collect(),
+            raw_headers: Vec::new(),
+            delimiter: b',',
+        }
+    }
+    
+    #[test]
+    fn merge_replaces_matching_rows_and_appends_the_rest() {
+        let primary = table(&["Bib", "Name", "Result"], &[&["1", "Anna", "90"], &["2", "Eero", "85"]]);
+        let secondary = table(&["Bib", "Name", "Result"], &[&["2", "Eero", "88"], &["3", "Mia", "70"]]);
+        let merged = merge_tables(primary, &secondary, "Bib", MergeStrategy::ReplaceAndAppend).unwrap();
+        
+        assert_eq!(merged.rows, [["1", "Anna", "90"], ["2", "Eero", "88"], ["3", "Mia", "70"]]);
+    }
+    
+    #[test]
+    fn merge_replace_only_drops_unmatched_rows() {
+        let primary = table(&["Bib", "Name", "Result"], &[&["1", "Anna", "90"], &["2", "Eero", "85"]]);
+        let secondary = table(&["Bib", "Name", "Result"], &[&["2", "Eero", "88"], &["3", "Mia", "70"]]);
+        let merged = merge_tables(primary, &secondary, "Bib", MergeStrategy::ReplaceOnly).unwrap();
+        
+        assert_eq!(merged.rows, [["1", "Anna", "90"], ["2", "Eero", "88"]]);
+    }
+    
+    #[test]
+    fn merge_aligns_secondary_columns_by_header() {
+        let primary = table(&["Bib", "Name", "Result"], &[&["1", "Anna", "90"]]);
+        // Different order, an extra column and no Name
+        let secondary = table(&["Result", "Club", "Bib"], &[&["95", "HKV", "1"], &["60", "TKV", "4"]]);
+        let merged = merge_tables(primary, &secondary, "Bib", MergeStrategy::ReplaceAndAppend).unwrap();
+        
+        assert_eq!(merged.headers, ["Bib", "Name", "Result"]);
+        assert_eq!(merged.rows, [["1", "", "95"], ["4", "", "60"]]);
+    }
+    
+    #[test]
+    fn merge_requires_the_key_column_in_both_sources() {
+        let primary = table(&["Bib", "Name"], &[&["1", "Anna"]]);
+        let secondary = table(&["Name"], &[&["Anna"]]);
+        let err = merge_tables(primary.clone(), &secondary, "Bib", MergeStrategy::ReplaceOnly).unwrap_err();
+        assert_eq!(err.to_string(), "Key column 'Bib' not found in secondary source");
+        let err = merge_tables(secondary.clone(), &primary, "Bib", MergeStrategy::ReplaceOnly).unwrap_err();
+        assert_eq!(err.to_string(), "Key column 'Bib' not found in primary source");
+    }
 }
@@ -1,15 +1,210 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::PathBuf;
+use std::time::Duration;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
 
 #[derive(Debug, Clone)]
 pub enum DataSource {
     Local(PathBuf),
     Cloud(String, String),  // (url, sheet_name)
+    Url(String),  // Delimited text published at a plain HTTP(S) address
+    Workbook(PathBuf, usize),  // Excel .xlsx file and the index of the sheet to show
+    Inline(TableData),  // Typed in by hand, nothing to reload
+    // Rows from `secondary` override rows in `primary` with the same `key_col` value
+    Merged {
+        primary: Box<DataSource>,
+        secondary: Box<DataSource>,
+        key_col: String,
+        strategy: MergeStrategy,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeStrategy {
+    ReplaceAndAppend,  // Also add rows that only exist in the secondary source
+    ReplaceOnly,
+}
+
+impl DataSource {
+    // Stable identifier for per-source caches and settings
+    pub fn cache_key(&self) -> String {
+        match self {
+            DataSource::Local(path) => format!("file:{}", path.display()),
+            DataSource::Cloud(url, sheet) => format!("sheet:{}#{}", url, sheet),
+            DataSource::Url(url) => format!("url:{}", url),
+            DataSource::Workbook(path, sheet_index) => format!("xlsx:{}#{}", path.display(), sheet_index),
+            DataSource::Inline(_) => "inline".to_string(),
+            DataSource::Merged { primary, secondary, key_col, strategy } => format!(
+                "merged:{}+{}@{}:{:?}", primary.cache_key(), secondary.cache_key(), key_col, strategy
+            ),
+        }
+    }
+}
+
+// Summary shown for a column in the pinned total row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TotalOp {
+    Sum,
+    Average,
+    Max,
+    Min,
+    Count,
+    None,
+}
+
+impl TotalOp {
+    pub const ALL: [TotalOp; 6] = [TotalOp::Sum, TotalOp::Average, TotalOp::Max, TotalOp::Min, TotalOp::Count, TotalOp::None];
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            TotalOp::Sum => "Sum",
+            TotalOp::Average => "Average",
+            TotalOp::Max => "Max",
+            TotalOp::Min => "Min",
+            TotalOp::Count => "Count",
+            TotalOp::None => "None",
+        }
+    }
+    
+    pub fn apply(self, values: &[f64]) -> Option<f64> {
+        if values.is_empty() {
+            return None;
+        }
+        match self {
+            TotalOp::Sum => Some(values.iter().sum()),
+            TotalOp::Average => Some(values.iter().sum::<f64>() / values.len() as f64),
+            TotalOp::Max => Some(values.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+            TotalOp::Min => Some(values.iter().copied().fold(f64::INFINITY, f64::min)),
+            TotalOp::Count => Some(values.len() as f64),
+            TotalOp::None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+// Periodic snapshots of the displayed table
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportSchedule {
+    pub output_dir: PathBuf,
+    pub format: ExportFormat,
+    pub interval: Duration,
+    pub filename_template: String,  // Supports {date}, {time} and {source_name}
+}
+
+impl Default for ExportSchedule {
+    fn default() -> Self {
+        ExportSchedule {
+            output_dir: PathBuf::new(),
+            format: ExportFormat::Csv,
+            interval: Duration::from_secs(300),
+            filename_template: "{source_name}_{date}_{time}".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    pub comment_char: Option<u8>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            comment_char: Some(b'#'),
+        }
+    }
+}
+
+// Everything a load needs besides the source, cloned so loads can run off the UI thread
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    pub csv_options: CsvOptions,
+    pub header_config: HeaderConfig,
+    pub sheet_cache_ttl: Duration,
+}
+
+// Outcome of a conditional fetch: new data, or the server's word that nothing changed
+#[derive(Debug, Clone)]
+pub enum FetchResult {
+    Updated(TableData),
+    Unchanged,
+}
+
+// Which raw columns to hide and how to rename the rest
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderConfig {
+    pub hidden_columns: Vec<String>,
+    pub numbered_prefixes: Vec<(String, String)>,  // ("part-", "S") turns "part-3" into "S3"
+    pub replacements: Vec<(String, String)>,
+    pub column_overrides: HashMap<String, bool>,  // Raw header → shown, taking precedence over `hidden_columns`
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleStrategy {
+    First,
+    Random(u64),        // seed
+    Stratified(usize),  // column index
+}
+
+// Adjacent columns sharing a one-letter prefix, like S1, S2, S3
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnGroup {
+    pub prefix: String,
+    pub columns: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnType {
+    Integer,
+    Float,
+    Text,
+    Empty,
+}
+
+impl ColumnType {
+    pub fn label(self) -> &'static str {
+        match self {
+            ColumnType::Integer => "Integer",
+            ColumnType::Float => "Float",
+            ColumnType::Text => "Text",
+            ColumnType::Empty => "Empty",
+        }
+    }
+}
+
+// Summary of a numeric column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnStatistics {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,  // Sample standard deviation; 0 for a single value
 }
 
 #[derive(Debug, Clone)]
 pub struct TableData {
     pub headers: Vec<String>,
     pub rows: Vec<Vec<String>>,
+    pub raw_headers: Vec<String>,  // before hiding and renaming
+    pub delimiter: u8,  // as detected when loading, so exports can use the same one
 }
 
 impl TableData {
@@ -17,6 +212,334 @@ impl TableData {
         TableData {
             headers: Vec::new(),
             rows: Vec::new(),
+            raw_headers: Vec::new(),
+            delimiter: b',',
+        }
+    }
+    
+    // Move column `from` to position `to`, shifting the columns in between
+    pub fn reorder_columns(&mut self, from: usize, to: usize) {
+        if from >= self.headers.len() || to >= self.headers.len() || from == to {
+            return;
+        }
+        let header = self.headers.remove(from);
+        self.headers.insert(to, header);
+        for row in &mut self.rows {
+            if row.len() < self.headers.len() {
+                row.resize(self.headers.len(), String::new());
+            }
+            let cell = row.remove(from);
+            row.insert(to, cell);
+        }
+    }
+    
+    // Rebuild the columns in `order`, given as current column indices
+    pub fn arrange_columns(&mut self, order: &[usize]) {
+        self.headers = order.iter().map(|&col| self.headers[col].clone()).collect();
+        for row in &mut self.rows {
+            *row = order.iter().map(|&col| row.get(col).cloned().unwrap_or_default()).collect();
+        }
+    }
+    
+    // Identifies a row across refreshes: Name + Surname when present, otherwise the first column
+    pub fn row_key(&self, row: &[String]) -> String {
+        let key_columns: Vec<usize> = ["Name", "Surname"].iter()
+            .filter_map(|name| self.headers.iter().position(|header| header == name))
+            .collect();
+        let key_columns = if key_columns.is_empty() { vec![0] } else { key_columns };
+        
+        key_columns.iter()
+            .map(|&col| row.get(col).map_or("", String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+    
+    // Runs of two or more adjacent columns named <letter><number> with the same letter
+    pub fn prefix_groups(&self) -> Vec<ColumnGroup> {
+        let prefix_of = |header: &str| -> Option<char> {
+            let mut chars = header.chars();
+            let letter = chars.next().filter(char::is_ascii_alphabetic)?;
+            let number = chars.as_str();
+            (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit())).then_some(letter)
+        };
+        
+        let mut groups = Vec::new();
+        let mut start = 0;
+        while start < self.headers.len() {
+            let Some(prefix) = prefix_of(&self.headers[start]) else {
+                start += 1;
+                continue;
+            };
+            let end = (start + 1..self.headers.len())
+                .find(|&col| prefix_of(&self.headers[col]) != Some(prefix))
+                .unwrap_or(self.headers.len());
+            if end - start >= 2 {
+                groups.push(ColumnGroup { prefix: prefix.to_string(), columns: start..end });
+            }
+            start = end;
+        }
+        groups
+    }
+    
+    // Fill blank cells in `col` with the nearest non-blank value above, undoing Excel-style merged cells
+    pub fn fill_down(&mut self, col: usize) {
+        let mut last = None;
+        for row in &mut self.rows {
+            let Some(cell) = row.get_mut(col) else {
+                continue;
+            };
+            if cell.trim().is_empty() {
+                if let Some(value) = &last {
+                    cell.clone_from(value);
+                }
+            } else {
+                last = Some(cell.clone());
+            }
+        }
+    }
+    
+    // Narrowest type that fits every non-empty cell of `col`
+    pub fn column_type(&self, col: usize) -> ColumnType {
+        let mut column_type = ColumnType::Empty;
+        for cell in self.rows.iter().filter_map(|row| row.get(col)).map(|cell| cell.trim()) {
+            if cell.is_empty() {
+                continue;
+            }
+            if cell.parse::<i64>().is_ok() {
+                if column_type == ColumnType::Empty {
+                    column_type = ColumnType::Integer;
+                }
+            } else if cell.parse::<f64>().is_ok() {
+                column_type = ColumnType::Float;
+            } else {
+                return ColumnType::Text;
+            }
+        }
+        column_type
+    }
+    
+    // First `n` non-empty cells of `col`
+    pub fn sample_values(&self, col: usize, n: usize) -> Vec<&str> {
+        self.rows.iter()
+            .filter_map(|row| row.get(col))
+            .map(|cell| cell.trim())
+            .filter(|cell| !cell.is_empty())
+            .take(n)
+            .collect()
+    }
+    
+    // Fraction of rows whose cell in `col` is blank
+    pub fn empty_ratio(&self, col: usize) -> f64 {
+        if self.rows.is_empty() {
+            return 0.0;
+        }
+        let empty = self.rows.iter()
+            .filter(|row| row.get(col).map_or("", |cell| cell.trim()).is_empty())
+            .count();
+        empty as f64 / self.rows.len() as f64
+    }
+    
+    // Most digits after the decimal point among the numeric cells of `col`
+    pub fn max_decimal_places(&self, col: usize) -> usize {
+        self.rows.iter()
+            .filter_map(|row| row.get(col))
+            .map(|cell| cell.trim())
+            .filter(|cell| cell.parse::<f64>().is_ok())
+            .map(|cell| cell.split_once('.').map_or(0, |(_, decimals)| decimals.len()))
+            .max()
+            .unwrap_or(0)
+    }
+    
+    // Parsed values of a column, or None if any non-empty cell isn't a number
+    pub fn numeric_values(&self, col: usize) -> Option<Vec<f64>> {
+        let mut values = Vec::new();
+        for cell in self.rows.iter().filter_map(|row| row.get(col)) {
+            let cell = cell.trim();
+            if cell.is_empty() {
+                continue;
+            }
+            values.push(cell.parse::<f64>().ok()?);
+        }
+        (!values.is_empty()).then_some(values)
+    }
+    
+    pub fn column_statistics(&self, col: usize) -> Option<ColumnStatistics> {
+        let mut values = self.numeric_values(col)?;
+        values.sort_by(f64::total_cmp);
+        let count = values.len();
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let median = if count % 2 == 0 {
+            (values[count / 2 - 1] + values[count / 2]) / 2.0
+        } else {
+            values[count / 2]
+        };
+        let stddev = if count > 1 {
+            let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (count - 1) as f64;
+            variance.sqrt()
+        } else {
+            0.0
+        };
+        Some(ColumnStatistics {
+            count,
+            min: values[0],
+            max: values[count - 1],
+            mean,
+            median,
+            stddev,
+        })
+    }
+    
+    // Take a subset of at most `n` rows for quick previews
+    pub fn sample_rows(&self, n: usize, strategy: SampleStrategy) -> TableData {
+        let indices: Vec<usize> = match strategy {
+            SampleStrategy::First => (0..self.rows.len().min(n)).collect(),
+            SampleStrategy::Random(seed) => {
+                let mut rng = SmallRng::seed_from_u64(seed);
+                let mut picked = rand::seq::index::sample(&mut rng, self.rows.len(), n.min(self.rows.len()))
+                    .into_vec();
+                // Keep the sampled rows in their original order
+                picked.sort_unstable();
+                picked
+            },
+            SampleStrategy::Stratified(col) => {
+                // Group row indices by value, keeping groups in order of first appearance
+                let mut groups: Vec<Vec<usize>> = Vec::new();
+                let mut group_index: HashMap<&str, usize> = HashMap::new();
+                for (i, row) in self.rows.iter().enumerate() {
+                    let value = row.get(col).map_or("", String::as_str);
+                    let group = *group_index.entry(value).or_insert_with(|| {
+                        groups.push(Vec::new());
+                        groups.len() - 1
+                    });
+                    groups[group].push(i);
+                }
+                
+                // Deal rows out one per group per round, so the groups get even shares and
+                // the ones too small for theirs leave the remainder to the rest
+                let target = n.min(self.rows.len());
+                let mut picked = Vec::with_capacity(target);
+                let mut round = 0;
+                while picked.len() < target {
+                    for group in &groups {
+                        if picked.len() == target {
+                            break;
+                        }
+                        if let Some(&i) = group.get(round) {
+                            picked.push(i);
+                        }
+                    }
+                    round += 1;
+                }
+                picked.sort_unstable();
+                picked
+            },
+        };
+        
+        TableData {
+            headers: self.headers.clone(),
+            rows: indices.into_iter().map(|i| self.rows[i].clone()).collect(),
+            raw_headers: self.raw_headers.clone(),
+            delimiter: self.delimiter,
+        }
+    }
+    
+    // Compare the columns as loaded with `expected`, ignoring case and order.
+    // Each failure is a readable line: a missing or an unexpected column.
+    pub fn validate_schema(&self, expected: &[String]) -> Result<(), Vec<String>> {
+        let loaded = if self.raw_headers.is_empty() { &self.headers } else { &self.raw_headers };
+        let normalize = |header: &String| header.trim().to_lowercase();
+        let loaded_names: HashSet<String> = loaded.iter().map(normalize).collect();
+        let expected_names: HashSet<String> = expected.iter().map(normalize).collect();
+        
+        let mut failures: Vec<String> = expected.iter()
+            .filter(|header| !loaded_names.contains(&normalize(header)))
+            .map(|header| format!("Missing column \"{}\"", header.trim()))
+            .collect();
+        failures.extend(loaded.iter()
+            .filter(|header| !expected_names.contains(&normalize(header)))
+            .map(|header| format!("Unexpected column \"{}\"", header.trim())));
+        if failures.is_empty() { Ok(()) } else { Err(failures) }
+    }
+    
+    // One sub-table per distinct (trimmed) value of `col`, in order of first appearance
+    pub fn split_by_column(&self, col: usize) -> Vec<(String, TableData)> {
+        let mut groups: Vec<(String, TableData)> = Vec::new();
+        let mut group_index: HashMap<&str, usize> = HashMap::new();
+        for row in &self.rows {
+            let value = row.get(col).map_or("", |cell| cell.trim());
+            let group = *group_index.entry(value).or_insert_with(|| {
+                groups.push((value.to_string(), TableData {
+                    headers: self.headers.clone(),
+                    rows: Vec::new(),
+                    raw_headers: self.raw_headers.clone(),
+                    delimiter: self.delimiter,
+                }));
+                groups.len() - 1
+            });
+            groups[group].1.rows.push(row.clone());
         }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // One row per entry, with its position in the first column and the group in the second
+    fn table(groups: &[&str]) -> TableData {
+        TableData {
+            headers: vec!["Row".to_string(), "Category".to_string()],
+            rows: groups.iter().enumerate()
+                .map(|(i, group)| vec![i.to_string(), group.to_string()])
+                .collect(),
+            raw_headers: Vec::new(),
+            delimiter: b',',
+        }
+    }
+    
+    fn row_numbers(data: &TableData) -> Vec<usize> {
+        data.rows.iter().map(|row| row[0].parse().unwrap()).collect()
+    }
+    
+    #[test]
+    fn first_takes_the_leading_rows() {
+        let data = table(&["A", "A", "B", "B", "C"]);
+        assert_eq!(row_numbers(&data.sample_rows(3, SampleStrategy::First)), vec![0, 1, 2]);
+        assert_eq!(data.sample_rows(10, SampleStrategy::First).rows.len(), 5);
+    }
+    
+    #[test]
+    fn random_is_repeatable_for_a_seed() {
+        let data = table(&["A"; 50]);
+        let sample = row_numbers(&data.sample_rows(10, SampleStrategy::Random(42)));
+        assert_eq!(sample, row_numbers(&data.sample_rows(10, SampleStrategy::Random(42))));
+        assert_eq!(sample.len(), 10);
+        // Distinct rows, in table order
+        assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+    
+    #[test]
+    fn stratified_returns_exactly_n_rows_across_groups() {
+        // 3 groups do not divide 10 evenly
+        let data = table(&["A", "B", "C"].repeat(10));
+        let sample = data.sample_rows(10, SampleStrategy::Stratified(1));
+        assert_eq!(sample.rows.len(), 10);
+        for group in ["A", "B", "C"] {
+            let count = sample.rows.iter().filter(|row| row[1] == group).count();
+            assert!((3..=4).contains(&count), "{} has {} rows", group, count);
+        }
+    }
+    
+    #[test]
+    fn stratified_gives_small_groups_share_to_the_rest() {
+        let mut groups = vec!["A"; 20];
+        groups.push("B");
+        let data = table(&groups);
+        let sample = data.sample_rows(6, SampleStrategy::Stratified(1));
+        assert_eq!(sample.rows.len(), 6);
+        assert_eq!(sample.rows.iter().filter(|row| row[1] == "B").count(), 1);
+        // Never more rows than the table has
+        assert_eq!(data.sample_rows(100, SampleStrategy::Stratified(1)).rows.len(), 21);
     }
 }
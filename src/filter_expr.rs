@@ -0,0 +1,321 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+// Boolean filter expressions over table rows, e.g. `result > 80 && club == "TeamA"`
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Compare(Operand, CompareOp, Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Column(usize),
+    Literal(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Number(String),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl FilterExpr {
+    // Parse an expression, resolving column names case-insensitively against `headers`
+    pub fn parse(input: &str, headers: &[String]) -> Result<FilterExpr, String> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0, headers };
+        let expr = parser.parse_or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(format!("Unexpected {}", parser.tokens[parser.pos]));
+        }
+        Ok(expr)
+    }
+    
+    pub fn matches(&self, row: &[String]) -> bool {
+        match self {
+            FilterExpr::And(left, right) => left.matches(row) && right.matches(row),
+            FilterExpr::Or(left, right) => left.matches(row) || right.matches(row),
+            FilterExpr::Not(inner) => !inner.matches(row),
+            FilterExpr::Compare(left, op, right) => op.holds(compare_values(left.value(row), right.value(row))),
+        }
+    }
+}
+
+impl CompareOp {
+    pub const ALL: [CompareOp; 6] = [CompareOp::Eq, CompareOp::Ne, CompareOp::Lt, CompareOp::Le, CompareOp::Gt, CompareOp::Ge];
+    
+    pub fn symbol(self) -> &'static str {
+        match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        }
+    }
+    
+    // Whether `left op right` is true, given how left compares to right
+    pub fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Eq => ordering == Ordering::Equal,
+            CompareOp::Ne => ordering != Ordering::Equal,
+            CompareOp::Lt => ordering == Ordering::Less,
+            CompareOp::Le => ordering != Ordering::Greater,
+            CompareOp::Gt => ordering == Ordering::Greater,
+            CompareOp::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+// How the token was written, for error messages
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Ident(text) | Token::Number(text) => write!(f, "'{}'", text),
+            Token::Str(text) => write!(f, "\"{}\"", text),
+            Token::Op(op) => write!(f, "'{}'", op.symbol()),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Not => write!(f, "'!'"),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+        }
+    }
+}
+
+impl Operand {
+    fn value<'a>(&'a self, row: &'a [String]) -> &'a str {
+        match self {
+            Operand::Column(col) => row.get(*col).map_or("", String::as_str),
+            Operand::Literal(value) => value,
+        }
+    }
+}
+
+// Numbers compare numerically, everything else case-insensitively as text
+pub fn compare_values(left: &str, right: &str) -> Ordering {
+    match (left.trim().parse::<f64>(), right.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => left.to_lowercase().cmp(&right.to_lowercase()),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+    
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '(' => { tokens.push(Token::LParen); i += 1; },
+            ')' => { tokens.push(Token::RParen); i += 1; },
+            '&' if next == Some('&') => { tokens.push(Token::And); i += 2; },
+            '|' if next == Some('|') => { tokens.push(Token::Or); i += 2; },
+            '=' if next == Some('=') => { tokens.push(Token::Op(CompareOp::Eq)); i += 2; },
+            '!' if next == Some('=') => { tokens.push(Token::Op(CompareOp::Ne)); i += 2; },
+            '!' => { tokens.push(Token::Not); i += 1; },
+            '<' if next == Some('=') => { tokens.push(Token::Op(CompareOp::Le)); i += 2; },
+            '>' if next == Some('=') => { tokens.push(Token::Op(CompareOp::Ge)); i += 2; },
+            '<' => { tokens.push(Token::Op(CompareOp::Lt)); i += 1; },
+            '>' => { tokens.push(Token::Op(CompareOp::Gt)); i += 1; },
+            '"' | '\'' => {
+                let end = chars[i + 1..].iter()
+                    .position(|&ch| ch == c)
+                    .ok_or("Unterminated string literal")?;
+                tokens.push(Token::Str(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            },
+            _ if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                tokens.push(Token::Number(chars[start..i].iter().collect()));
+            },
+            _ if c.is_alphanumeric() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            },
+            _ => return Err(format!("Unexpected character '{}'", c)),
+        }
+    }
+    
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    headers: &'a [String],
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+    
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+    
+    fn parse_or(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = FilterExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+    
+    fn parse_and(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = FilterExpr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+    
+    fn parse_unary(&mut self) -> Result<FilterExpr, String> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(FilterExpr::Not(Box::new(self.parse_unary()?)))
+            },
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("Missing closing parenthesis".to_string()),
+                }
+            },
+            _ => self.parse_comparison(),
+        }
+    }
+    
+    fn parse_comparison(&mut self) -> Result<FilterExpr, String> {
+        let left = self.parse_operand()?;
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            Some(token) => return Err(format!("Expected comparison operator, found {}", token)),
+            None => return Err("Expected comparison operator".to_string()),
+        };
+        let right = self.parse_operand()?;
+        Ok(FilterExpr::Compare(left, op, right))
+    }
+    
+    fn parse_operand(&mut self) -> Result<Operand, String> {
+        match self.next() {
+            Some(Token::Ident(name)) => self.headers.iter()
+                .position(|header| header.eq_ignore_ascii_case(&name))
+                .map(Operand::Column)
+                .ok_or_else(|| format!("Unknown column '{}'", name)),
+            Some(Token::Str(value)) | Some(Token::Number(value)) => Ok(Operand::Literal(value)),
+            Some(token) => Err(format!("Expected column name or value, found {}", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn headers() -> Vec<String> {
+        ["Name", "Club", "Result"].iter().map(|header| header.to_string()).collect()
+    }
+    
+    fn row(name: &str, club: &str, result: &str) -> Vec<String> {
+        vec![name.to_string(), club.to_string(), result.to_string()]
+    }
+    
+    fn matches(input: &str, row: &[String]) -> bool {
+        FilterExpr::parse(input, &headers()).unwrap().matches(row)
+    }
+    
+    #[test]
+    fn and_binds_tighter_than_or() {
+        // Read as `Name == Anna || (Club == HKV && Result > 90)`
+        let expr = "Name == 'Anna' || Club == 'HKV' && Result > 90";
+        assert!(matches(expr, &row("Anna", "TPS", "50")));
+        assert!(!matches(expr, &row("Bea", "TPS", "95")));
+    }
+    
+    #[test]
+    fn parentheses_override_precedence() {
+        let expr = "(Name == 'Anna' || Club == 'HKV') && Result > 90";
+        assert!(!matches(expr, &row("Anna", "TPS", "50")));
+        assert!(matches(expr, &row("Bea", "HKV", "95")));
+    }
+    
+    #[test]
+    fn not_negates_the_following_term() {
+        assert!(matches("!(Club == 'HKV')", &row("Anna", "TPS", "50")));
+        assert!(!matches("!Club == 'HKV' && Result > 0", &row("Anna", "HKV", "50")));
+    }
+    
+    #[test]
+    fn quoted_strings_keep_spaces_and_operators() {
+        assert!(matches("Club == \"Team A && B\"", &row("Anna", "Team A && B", "50")));
+        assert!(matches("Name != 'Anna'", &row("Bea", "HKV", "50")));
+    }
+    
+    #[test]
+    fn negative_numbers_compare_numerically() {
+        assert!(matches("Result > -5", &row("Anna", "HKV", "-2.5")));
+        assert!(!matches("Result > -5", &row("Anna", "HKV", "-10")));
+    }
+    
+    #[test]
+    fn column_names_are_case_insensitive() {
+        assert!(matches("club == 'hkv'", &row("Anna", "HKV", "50")));
+        assert!(matches("RESULT >= 50", &row("Anna", "HKV", "50")));
+    }
+    
+    #[test]
+    fn rejects_unknown_columns() {
+        assert_eq!(FilterExpr::parse("Age > 20", &headers()), Err("Unknown column 'Age'".to_string()));
+    }
+    
+    #[test]
+    fn rejects_unterminated_strings() {
+        assert_eq!(FilterExpr::parse("Club == 'HKV", &headers()), Err("Unterminated string literal".to_string()));
+    }
+    
+    #[test]
+    fn errors_show_tokens_as_written() {
+        assert_eq!(FilterExpr::parse("Club 'HKV'", &headers()), Err("Expected comparison operator, found \"HKV\"".to_string()));
+        assert_eq!(FilterExpr::parse("Result > 5 )", &headers()), Err("Unexpected ')'".to_string()));
+    }
+}
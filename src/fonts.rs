@@ -0,0 +1,16 @@
+use eframe::egui::{self, FontData, FontDefinitions, FontFamily};
+
+// DejaVu Sans covers Latin Extended, Greek and Cyrillic, so athlete names with diacritics
+// render even when the default fonts lack a glyph (license: assets/fonts/LICENSE-DejaVu.txt)
+const FALLBACK_FONT_NAME: &str = "DejaVuSans";
+pub const FALLBACK_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+
+// Append the bundled font to both families; egui only uses it for glyphs the default fonts lack
+pub fn install_fallback_font(ctx: &egui::Context) {
+    let mut fonts = FontDefinitions::default();
+    fonts.font_data.insert(FALLBACK_FONT_NAME.to_string(), FontData::from_static(FALLBACK_FONT));
+    for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        fonts.families.entry(family).or_default().push(FALLBACK_FONT_NAME.to_string());
+    }
+    ctx.set_fonts(fonts);
+}
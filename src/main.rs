@@ -1,15 +1,96 @@
+mod app_log;
+mod comments;
 mod data_types;
 mod data_handler;
+mod filter_expr;
+mod fonts;
+mod pdf_export;
+mod rich_text;
+mod settings;
+mod stream_output;
+mod theme;
+mod templates;
+mod thumbnail;
+mod translate;
+mod url_scheme;
 
-use data_handler::{load_csv_file, load_google_sheet};
-use data_types::{TableData, DataSource};
+use data_handler::{create_google_sheet, infer_column_types, is_column_hidden, is_xlsx_path, load_google_sheet, load_source, source_reachable, xlsx_sheet_names, replace_header, read_url_list, write_column_statistics, write_csv, write_table};
+use data_types::{TableData, DataSource, ColumnGroup, ColumnStatistics, ExportFormat, ExportSchedule, FetchResult, LoadOptions, MergeStrategy, SampleStrategy, CsvOptions, HeaderConfig, TotalOp};
+use templates::SPORT_TEMPLATES;
+use filter_expr::{compare_values, CompareOp, FilterExpr};
+use settings::{ColumnConfig, PrintHeader, SavedSource, Settings};
+use stream_output::{OutputResolution, StreamOutput};
 use eframe::{egui, Frame, App, CreationContext};
 use rfd::FileDialog;
-use std::path::PathBuf;
-use std::time::{Duration, Instant};
+use chrono::NaiveDate;
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use std::fmt::Write as _;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
 const VERSION: &str = "1.0.0";
-const UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+const WAKE_DETECTION_INTERVALS: u32 = 3;  // A gap this many refresh intervals long means the system slept
+const DEFAULT_SHEET_CACHE_TTL: Duration = Duration::ZERO;  // Off: a cached response would hide live edits
+const PIN_UNLOCK_DURATION: Duration = Duration::from_secs(60);
+const PIN_ENV_VAR: &str = "SCORE_VIEWER_PIN";  // Hands the PIN to a duplicate window outside the visible command line
+const COMMENT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_WINDOW_SIZE: egui::Vec2 = egui::vec2(1024.0, 768.0);
+const FULLSCREEN_CONTROLS_TIMEOUT: Duration = Duration::from_secs(3);
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const HEALTH_CHECK_FAILURE_LIMIT: u32 = 2;  // Consecutive failed checks before warning
+const DEFAULT_ROW_HEIGHT: f32 = 18.0;
+const DEFAULT_FONT_SIZE: f32 = 12.5;
+const ACCESSIBLE_ROW_HEIGHT: f32 = 28.0;
+const ACCESSIBLE_FONT_SIZE: f32 = 20.0;
+const MIN_ROW_HEIGHT: f32 = 8.0;
+const MAX_ROW_HEIGHT: f32 = 40.0;
+const DEFAULT_PREVIEW_ROWS: usize = 20;
+const HISTOGRAM_BINS: usize = 10;
+const HISTOGRAM_SIZE: egui::Vec2 = egui::vec2(100.0, 32.0);
+const DEFAULT_DISTRIBUTION_BINS: usize = 20;
+const NORMAL_CURVE_POINTS: usize = 200;
+const STATUS_MESSAGE_DURATION: Duration = Duration::from_secs(2);
+const UNDO_HISTORY_LIMIT: usize = 50;
+const AUTO_HIDE_EMPTY_RATIO: f64 = 0.95;
+const LEADERBOARD_SIZE: usize = 3;
+const QR_MIN_SIZE: f32 = 300.0;
+const QR_QUIET_ZONE: usize = 4;  // Modules of white border the spec asks for
+const DATA_AGE_FRESH: Duration = Duration::from_secs(30);
+const DATA_AGE_STALE: Duration = Duration::from_secs(120);
+const RESULT_COLUMN: &str = "Result";
+const RANK_COLUMNS: [&str; 2] = ["Rank", "Posit."];
+const RANK_BAR_GOLD: egui::Color32 = egui::Color32::from_rgb(212, 175, 55);
+const RANK_BAR_GRAY: egui::Color32 = egui::Color32::from_rgb(128, 128, 128);
+const MAX_RESULT_DECIMALS: usize = 6;
+const HEADER_SAMPLE_VALUES: usize = 5;
+const ANNOTATION_OFFSET: egui::Vec2 = egui::vec2(40.0, -28.0);  // From the cell's top-right corner to the label
+const ANNOTATION_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 60, 60);
+const SKELETON_ROWS: usize = 12;
+const SKELETON_COLUMNS: usize = 6;
+const LOADING_FADE_SECONDS: f32 = 0.3;
+const DEFAULT_FREEZE_SEPARATOR_WIDTH: f32 = 2.0;
+const BREAKPOINT_WINDOW_WIDTH: f32 = 800.0;
+const CHANGE_FLASH_DURATION: Duration = Duration::from_millis(500);
+const CHANGE_FLASH_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 200, 0);
+const SEARCH_MATCH_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(120, 110, 0, 120);
+const SEARCH_FOCUS_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 140, 0);
+const DEFAULT_FREEZE_SEPARATOR_COLOR: egui::Color32 = egui::Color32::from_rgb(0, 92, 128);
+const PROGRESS_COMPLETE_COLOR: egui::Color32 = egui::Color32::from_rgb(60, 170, 80);
+const PROGRESS_PARTIAL_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 180, 40);
+const PROGRESS_BEHIND_COLOR: egui::Color32 = egui::Color32::from_rgb(200, 60, 60);
+const PROGRESS_BAR_WIDTH: f32 = 110.0;
+const GOLD_HIGHLIGHT: egui::Color32 = egui::Color32::from_rgba_premultiplied(120, 100, 20, 120);
+const SILVER_HIGHLIGHT: egui::Color32 = egui::Color32::from_rgba_premultiplied(100, 100, 110, 120);
+const BRONZE_HIGHLIGHT: egui::Color32 = egui::Color32::from_rgba_premultiplied(110, 65, 25, 120);
+const PB_HIGHLIGHT: egui::Color32 = egui::Color32::from_rgba_premultiplied(30, 100, 50, 120);
+const PB_MARKER: &str = "PB";
+
+// (row key, column name) identifying a cell across refreshes and row moves
+type CellKey = (String, String);
 
 struct ScoreViewer {
     data_source: Option<DataSource>,
@@ -19,9 +100,324 @@ struct ScoreViewer {
     sheet_url: String,
     sheet_name: String,
     show_cloud_dialog: bool,
+    pending_workbook: Option<(PathBuf, Vec<String>)>,  // Workbook waiting for a sheet to be picked, with its sheet names
+    temp_sheet_index: usize,
+    show_url_dialog: bool,
+    temp_csv_url: String,
     last_update: Instant,
+    last_update_wall: SystemTime,  // Instant may stop during sleep, so wake detection uses wall time
+    refresh_paused: bool,
     temp_url: String,
     temp_sheet: String,
+    row_height: f32,
+    font_size: f32,
+    fit_to_screen: bool,
+    last_modified: Option<SystemTime>,
+    show_preview_dialog: bool,
+    preview_size: usize,
+    preview_strategy: SampleStrategy,
+    preview_data: Option<TableData>,
+    frozen_columns: usize,
+    freeze_separator_color: egui::Color32,
+    freeze_separator_width: f32,
+    show_display_settings: bool,
+    csv_options: CsvOptions,
+    show_csv_options_dialog: bool,
+    temp_csv_options: CsvOptions,
+    temp_comment_char: String,
+    cell_change_times: HashMap<(usize, usize), Instant>,
+    column_priority: Vec<u8>,
+    expression_filter: String,
+    filter_expr: Option<FilterExpr>,
+    filter_error: Option<String>,
+    custom_visuals: Option<egui::Visuals>,
+    show_theme_editor: bool,
+    offline_mode: bool,
+    offline_cache: HashMap<String, (TableData, SystemTime)>,
+    offline_cached_at: Option<SystemTime>,
+    edit_mode: bool,
+    drag_row: Option<usize>,
+    drop_target_row: Option<usize>,
+    pending_row_move: Option<(usize, usize)>,
+    drag_column: Option<usize>,  // Header being dragged to swap it with another
+    drop_target_column: Option<usize>,
+    pending_column_swap: Option<(usize, usize)>,
+    undo_history: Vec<UndoSnapshot>,
+    accessibility_mode: bool,
+    header_config: HeaderConfig,
+    template_index: usize,
+    auto_detect_template: bool,
+    template_detected_for: Option<String>,
+    column_config_restored_for: Option<String>,
+    reload_requested: bool,
+    status_message: Option<(String, Instant)>,
+    show_stats_panel: bool,
+    locked_rows: HashSet<String>,
+    show_merge_dialog: bool,
+    merge_from_sheet: bool,
+    merge_path: String,
+    merge_url: String,
+    merge_sheet: String,
+    merge_key_col: String,
+    merge_strategy: MergeStrategy,
+    cell_history: HashMap<CellKey, Vec<(Instant, String)>>,
+    history_cell: Option<CellKey>,
+    // Row keys of the two rows shown side by side
+    compared_rows: Option<(String, String)>,
+    custom_row_actions: Vec<RowAction>,
+    table_caption: String,
+    show_caption_dialog: bool,
+    temp_caption: String,
+    auto_hidden_columns: HashSet<String>,
+    column_visibility: HashMap<String, bool>,  // User choices, overriding auto-hiding
+    column_overrides: HashMap<String, bool>,  // Raw header → shown, overriding the template's hidden columns
+    scroll_to_top: bool,
+    column_presets: Vec<ColumnPreset>,
+    active_preset: Option<usize>,
+    show_presets_dialog: bool,
+    temp_preset_name: String,
+    loading: bool,
+    sort_keys: Vec<(usize, bool)>,  // (column, descending), most significant first
+    show_sort_dialog: bool,
+    temp_sort_spec: String,
+    sort_errors: Vec<String>,
+    source_locked: bool,
+    show_unlock_confirm: bool,
+    window_title_locked: bool,
+    show_aggregate_sidebar: bool,
+    aggregate_column: Option<String>,
+    show_distribution_window: bool,
+    distribution_column: Option<String>,  // By name so the choice survives column reordering
+    distribution_bins: usize,
+    result_decimals: Option<usize>,
+    precision_detected_for: Option<String>,
+    show_precision_dialog: bool,
+    temp_result_decimals: usize,
+    tabs: Vec<TabState>,
+    active_tab: Option<usize>,  // None while the table on screen has no tab of its own
+    new_tab_requested: bool,  // Opened from the tab bar's "+": the current view keeps a tab once the new source starts loading
+    import_queue: VecDeque<usize>,
+    import_total: usize,  // Sheets in the URL list being imported
+    show_search_bar: bool,
+    search_query: String,
+    search_matches: Vec<(usize, usize)>,  // (row, column) in display order
+    search_focus: Option<usize>,
+    scroll_to_search_focus: bool,
+    parse_time: Option<Duration>,
+    data_bytes: usize,
+    show_total_row: bool,
+    total_row_ops: Vec<TotalOp>,
+    column_left: HashMap<usize, f32>,  // Screen x of each rendered column, for aligning the total row
+    forwarded_links: Option<Receiver<String>>,
+    show_rank_bars: bool,
+    show_dnx_strikethrough: bool,
+    temp_dnx_patterns: String,  // Comma-separated, while the settings dialog is open
+    show_table_editor: bool,
+    editor_cells: Vec<Vec<String>>,  // First row holds the headers
+    pending_editor_focus: Option<egui::Id>,
+    show_reorder_dialog: bool,
+    temp_column_order: Vec<usize>,  // Current column indices in their new order
+    temp_column_targets: Vec<usize>,  // 1-based destination typed next to each entry
+    column_order: Vec<usize>,  // Loaded column shown at each position; empty until reordered by hand
+    column_types: Vec<bool>,  // Numeric columns, which are right-aligned
+    hidden_rows: HashSet<String>,  // Row keys hidden by hand
+    auto_hide_expression: String,
+    auto_hide_filter: Option<FilterExpr>,
+    auto_hidden_rows: HashSet<String>,  // Row keys matching `auto_hide_filter` in the current data
+    show_auto_hide_dialog: bool,
+    temp_auto_hide: String,
+    auto_hide_error: Option<String>,
+    last_successful_load: Option<Instant>,
+    show_qr_window: bool,
+    qr_code: Option<(egui::TextureHandle, String)>,  // The code and the URL it encodes
+    export_schedule: Option<ExportSchedule>,
+    last_export: Option<Instant>,
+    last_export_label: Option<String>,
+    show_export_schedule_dialog: bool,
+    sheet_cache_ttl: Duration,
+    show_log_panel: bool,
+    temp_export_schedule: ExportSchedule,
+    temp_export_enabled: bool,
+    readonly_pin: Option<String>,
+    pin_unlocked_until: Option<Instant>,
+    pending_pin_action: Option<PinAction>,
+    temp_pin: String,
+    auto_group_prefixes: bool,
+    column_groups: Vec<ColumnGroup>,
+    collapsed_groups: HashSet<String>,  // By prefix, so collapsing survives refreshes
+    auto_merge_first_col: bool,
+    selected_rows: HashSet<usize>,
+    selection_anchor: Option<usize>,  // Row a Shift+Click range starts from
+    scroll_to_row: Option<usize>,  // Data row to bring into view on the next frame
+    export_column_stats: bool,
+    show_athlete_profile: bool,
+    athlete_query: String,
+    filter_text: String,
+    selected_categories: HashSet<String>,  // Empty shows every category
+    split_data: Option<TableData>,  // Right-hand table in split view
+    split_label: String,
+    split_drag_row: Option<usize>,
+    pending_split_move: Option<SplitMove>,
+    // Drop targets recorded while drawing: each table's area and its rows' vertical centers
+    main_table_rect: Option<egui::Rect>,
+    split_table_rect: Option<egui::Rect>,
+    main_row_centers: Vec<(usize, f32)>,
+    split_row_centers: Vec<(usize, f32)>,
+    receiver: Option<Receiver<FetchOutcome>>,
+    sheet_etag: Option<String>,  // Of the last Google Sheet fetch, so refreshes can skip unchanged sheets
+    pending_load: Option<PendingLoad>,
+    last_error: Option<String>,  // Why the most recent load failed, until the next one succeeds
+    settings: Settings,
+    show_settings_dialog: bool,
+    temp_settings: Settings,
+    annotations: Vec<TableAnnotation>,
+    annotation_cells: HashMap<(usize, usize), egui::Rect>,  // On-screen annotated cells, recorded while drawing
+    show_annotation_dialog: bool,
+    temp_annotation: TableAnnotation,
+    show_comment_panel: bool,
+    comments: VecDeque<(String, String)>,  // (username, message), oldest first
+    comment_input: String,
+    comment_error: Option<String>,
+    last_comment_poll: Option<Instant>,
+    stream_output: Option<StreamOutput>,
+    translation_receiver: Option<Receiver<TranslationResult>>,
+    pending_translations: Vec<(String, String, bool)>,  // (raw header, translation, accepted)
+    translation_error: Option<String>,
+    show_translation_dialog: bool,
+    show_stream_dialog: bool,
+    show_sheet_export_dialog: bool,
+    temp_export_title: String,
+    google_access_token: String,  // OAuth2 bearer token, kept for this session only
+    sheet_export_receiver: Option<Receiver<SheetExportResult>>,
+    category_pdf_receiver: Option<Receiver<CategoryPdfProgress>>,
+    category_pdf_status: String,  // Latest progress line for the export dialog
+    compact_mode: bool,
+    compact_locked: bool,  // Started with --compact, so there is no way back to the full UI
+    fullscreen_table: bool,
+    window_fullscreen: bool,  // What the window was last told, so it only changes on a toggle
+    fullscreen_activity: Instant,  // Last mouse movement, to auto-hide the exit button
+    highlight_rules: Vec<HighlightRule>,  // The first matching rule colors the row
+    show_event_info_dialog: bool,
+    show_command_palette: bool,
+    loaded_source: Option<DataSource>,  // Where the table on screen came from, while another source loads
+    schema_errors: Vec<String>,  // Why strict mode rejected the last load; shown until dismissed
+    temp_expected_columns: String,  // Comma-separated, while the settings dialog is open
+    command_palette_query: String,
+    command_palette_selected: usize,  // Index into the filtered entries
+    window_geometry: Option<(egui::Vec2, Option<egui::Pos2>)>,  // Size and position of the normal (not fullscreen) window
+    last_health_check: Option<Instant>,
+    health_receiver: Option<Receiver<bool>>,
+    health_failures: u32,
+    temp_print_header: PrintHeader,
+    temp_event_date: String,
+    temp_date_format: String,
+    temp_stream_name: String,
+    temp_stream_resolution: OutputResolution,
+}
+
+// Callout label pinned to a cell (data row and column indices)
+#[derive(Clone)]
+struct TableAnnotation {
+    row: usize,
+    col: usize,
+    text: String,
+    color: egui::Color32,
+    arrow: bool,  // Draw a line from the label to the cell
+}
+
+// Rows whose cell in `column_index` compares to `threshold` like this get a background color
+#[derive(Clone)]
+struct HighlightRule {
+    column_index: usize,
+    comparator: CompareOp,
+    threshold: String,
+    color: egui::Color32,
+}
+
+type LoadResult = Result<TableData, String>;
+type FetchOutcome = Result<(FetchResult, Option<String>), String>;  // With the sheet's new ETag
+type TranslationResult = Result<Vec<(String, String)>, String>;
+type SheetExportResult = Result<String, String>;  // URL of the created spreadsheet
+
+// Messages from the background "Export All Categories as PDF" job
+enum CategoryPdfProgress {
+    Exporting { current: usize, total: usize, category: String },
+    Finished(Result<usize, String>),  // Number of files written
+}
+
+// What to do with the result of the load running in the background
+struct PendingLoad {
+    source: DataSource,
+    started: Instant,
+    modified: Option<SystemTime>,  // Of a local file, recorded before loading
+    replace: bool,  // A newly opened source rather than a refresh of the current one
+}
+
+// Both tables as they were before an edit
+struct UndoSnapshot {
+    data: TableData,
+    split_data: Option<TableData>,
+}
+
+// A row dragged from one split-view table to the other, waiting for confirmation
+#[derive(Clone, Copy)]
+struct SplitMove {
+    to_split: bool,
+    from: usize,
+    to: usize,
+}
+
+// Actions that change the data source or its contents, gated by the read-only PIN
+#[derive(Clone, Copy)]
+enum PinAction {
+    OpenFile,
+    OpenRecent(usize),  // Index into the recent files
+    ConnectSheet,
+    OpenUrl,
+    CreateTable,
+    ImportUrlList,
+    Merge,
+    EditMode,
+}
+
+// One open data source in the tab bar. The active tab's live table is `ScoreViewer::data`;
+// `data` here is its copy from when it was last in the background.
+struct TabState {
+    label: String,
+    source: DataSource,
+    data: Option<TableData>,
+    failed: bool,
+    last_update: Instant,  // Each tab refreshes on its own timer, also in the background
+    receiver: Option<Receiver<LoadResult>>,  // Background refresh in flight
+}
+
+// Named column visibility layout, activated with Ctrl + `key`
+struct ColumnPreset {
+    name: String,
+    key: char,
+    visible_columns: Vec<bool>,  // Indexed like the headers it was saved from
+}
+
+pub type RowCallback = Box<dyn Fn(&[Vec<String>]) + Send>;
+
+// An extra entry in the cell context menu, run against the rows it was opened on
+pub struct RowAction {
+    pub label: String,
+    pub callback: RowCallback,
+}
+
+// An action offered by the command palette (Ctrl+K)
+struct CommandPaletteEntry {
+    label: String,
+    action: fn(&mut ScoreViewer),
+}
+
+// Rows (after filtering) and sizing used to render one frame of the table
+struct TableView<'a> {
+    data: &'a TableData,
+    rows: Vec<usize>,
+    row_height: f32,
+    font_size: f32,
 }
 
 impl Default for ScoreViewer {
@@ -34,92 +430,953 @@ impl Default for ScoreViewer {
             sheet_url: String::new(),
             sheet_name: String::new(),
             show_cloud_dialog: false,
+            pending_workbook: None,
+            temp_sheet_index: 0,
+            show_url_dialog: false,
+            temp_csv_url: String::new(),
             last_update: Instant::now(),
+            last_update_wall: SystemTime::now(),
+            refresh_paused: false,
             temp_url: String::new(),
             temp_sheet: String::new(),
+            row_height: DEFAULT_ROW_HEIGHT,
+            font_size: DEFAULT_FONT_SIZE,
+            fit_to_screen: false,
+            last_modified: None,
+            show_preview_dialog: false,
+            preview_size: DEFAULT_PREVIEW_ROWS,
+            preview_strategy: SampleStrategy::First,
+            preview_data: None,
+            frozen_columns: 0,
+            freeze_separator_color: DEFAULT_FREEZE_SEPARATOR_COLOR,
+            freeze_separator_width: DEFAULT_FREEZE_SEPARATOR_WIDTH,
+            show_display_settings: false,
+            csv_options: CsvOptions::default(),
+            show_csv_options_dialog: false,
+            temp_csv_options: CsvOptions::default(),
+            temp_comment_char: String::new(),
+            cell_change_times: HashMap::new(),
+            column_priority: Vec::new(),
+            expression_filter: String::new(),
+            filter_expr: None,
+            filter_error: None,
+            custom_visuals: None,
+            show_theme_editor: false,
+            offline_mode: true,
+            offline_cache: HashMap::new(),
+            offline_cached_at: None,
+            edit_mode: false,
+            drag_row: None,
+            drop_target_row: None,
+            pending_row_move: None,
+            drag_column: None,
+            drop_target_column: None,
+            pending_column_swap: None,
+            undo_history: Vec::new(),
+            accessibility_mode: false,
+            header_config: HeaderConfig::default(),
+            template_index: 0,
+            auto_detect_template: true,
+            template_detected_for: None,
+            column_config_restored_for: None,
+            reload_requested: false,
+            status_message: None,
+            show_stats_panel: false,
+            locked_rows: HashSet::new(),
+            show_merge_dialog: false,
+            merge_from_sheet: true,
+            merge_path: String::new(),
+            merge_url: String::new(),
+            merge_sheet: String::new(),
+            merge_key_col: String::new(),
+            merge_strategy: MergeStrategy::ReplaceAndAppend,
+            cell_history: HashMap::new(),
+            history_cell: None,
+            compared_rows: None,
+            custom_row_actions: Vec::new(),
+            table_caption: String::new(),
+            show_caption_dialog: false,
+            temp_caption: String::new(),
+            auto_hidden_columns: HashSet::new(),
+            column_visibility: HashMap::new(),
+            column_overrides: HashMap::new(),
+            scroll_to_top: false,
+            column_presets: Vec::new(),
+            active_preset: None,
+            show_presets_dialog: false,
+            temp_preset_name: String::new(),
+            loading: false,
+            sort_keys: Vec::new(),
+            show_sort_dialog: false,
+            temp_sort_spec: String::new(),
+            sort_errors: Vec::new(),
+            source_locked: false,
+            show_unlock_confirm: false,
+            window_title_locked: false,
+            show_aggregate_sidebar: false,
+            aggregate_column: None,
+            show_distribution_window: false,
+            distribution_column: None,
+            distribution_bins: DEFAULT_DISTRIBUTION_BINS,
+            result_decimals: None,
+            precision_detected_for: None,
+            show_precision_dialog: false,
+            temp_result_decimals: 0,
+            tabs: Vec::new(),
+            active_tab: None,
+            new_tab_requested: false,
+            import_queue: VecDeque::new(),
+            import_total: 0,
+            show_search_bar: false,
+            search_query: String::new(),
+            search_matches: Vec::new(),
+            search_focus: None,
+            scroll_to_search_focus: false,
+            parse_time: None,
+            data_bytes: 0,
+            show_total_row: true,
+            total_row_ops: Vec::new(),
+            column_left: HashMap::new(),
+            forwarded_links: None,
+            show_rank_bars: false,
+            show_dnx_strikethrough: true,
+            temp_dnx_patterns: String::new(),
+            show_table_editor: false,
+            editor_cells: Vec::new(),
+            pending_editor_focus: None,
+            show_reorder_dialog: false,
+            temp_column_order: Vec::new(),
+            temp_column_targets: Vec::new(),
+            column_order: Vec::new(),
+            column_types: Vec::new(),
+            hidden_rows: HashSet::new(),
+            auto_hide_expression: String::new(),
+            auto_hide_filter: None,
+            auto_hidden_rows: HashSet::new(),
+            show_auto_hide_dialog: false,
+            temp_auto_hide: String::new(),
+            auto_hide_error: None,
+            last_successful_load: None,
+            show_qr_window: false,
+            qr_code: None,
+            export_schedule: None,
+            last_export: None,
+            last_export_label: None,
+            show_export_schedule_dialog: false,
+            sheet_cache_ttl: DEFAULT_SHEET_CACHE_TTL,
+            show_log_panel: false,
+            temp_export_schedule: ExportSchedule::default(),
+            temp_export_enabled: false,
+            readonly_pin: None,
+            pin_unlocked_until: None,
+            pending_pin_action: None,
+            temp_pin: String::new(),
+            auto_group_prefixes: true,
+            column_groups: Vec::new(),
+            collapsed_groups: HashSet::new(),
+            auto_merge_first_col: false,
+            selected_rows: HashSet::new(),
+            selection_anchor: None,
+            scroll_to_row: None,
+            export_column_stats: false,
+            show_athlete_profile: false,
+            athlete_query: String::new(),
+            filter_text: String::new(),
+            selected_categories: HashSet::new(),
+            split_data: None,
+            split_label: String::new(),
+            split_drag_row: None,
+            pending_split_move: None,
+            main_table_rect: None,
+            split_table_rect: None,
+            main_row_centers: Vec::new(),
+            split_row_centers: Vec::new(),
+            receiver: None,
+            sheet_etag: None,
+            pending_load: None,
+            last_error: None,
+            settings: Settings::default(),
+            show_settings_dialog: false,
+            temp_settings: Settings::default(),
+            annotations: Vec::new(),
+            annotation_cells: HashMap::new(),
+            show_annotation_dialog: false,
+            temp_annotation: TableAnnotation {
+                row: 0,
+                col: 0,
+                text: String::new(),
+                color: ANNOTATION_COLOR,
+                arrow: true,
+            },
+            show_comment_panel: false,
+            comments: VecDeque::new(),
+            comment_input: String::new(),
+            comment_error: None,
+            last_comment_poll: None,
+            stream_output: None,
+            translation_receiver: None,
+            pending_translations: Vec::new(),
+            translation_error: None,
+            show_translation_dialog: false,
+            show_stream_dialog: false,
+            show_sheet_export_dialog: false,
+            temp_export_title: String::new(),
+            google_access_token: String::new(),
+            sheet_export_receiver: None,
+            category_pdf_receiver: None,
+            category_pdf_status: String::new(),
+            compact_mode: false,
+            compact_locked: false,
+            fullscreen_table: false,
+            window_fullscreen: false,
+            fullscreen_activity: Instant::now(),
+            highlight_rules: Vec::new(),
+            show_event_info_dialog: false,
+            show_command_palette: false,
+            loaded_source: None,
+            schema_errors: Vec::new(),
+            temp_expected_columns: String::new(),
+            command_palette_query: String::new(),
+            command_palette_selected: 0,
+            window_geometry: None,
+            last_health_check: None,
+            health_receiver: None,
+            health_failures: 0,
+            temp_print_header: PrintHeader::default(),
+            temp_event_date: String::new(),
+            temp_date_format: String::new(),
+            temp_stream_name: "Score Viewer".to_string(),
+            temp_stream_resolution: OutputResolution::Hd1080,
         }
     }
 }
 
 impl App for ScoreViewer {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
+    fn on_close_event(&mut self) -> bool {
+        self.save_session();
+        true
+    }
+    
+    fn update(&mut self, ctx: &egui::Context, frame: &mut Frame) {
         let now = Instant::now();
         
+        // Re-lock once the PIN unlock window has passed
+        if let Some(until) = self.pin_unlocked_until {
+            if now >= until {
+                self.pin_unlocked_until = None;
+                self.set_edit_mode(false);
+            } else {
+                ctx.request_repaint_after(Duration::from_secs(1));
+            }
+        }
+        
+        let title_locked = self.source_locked || self.pin_locked();
+        if self.window_title_locked != title_locked {
+            self.window_title_locked = title_locked;
+            frame.set_window_title(if title_locked { "🔒 Score Viewer" } else { "Score Viewer" });
+        }
+        
+        // Remembered for the next launch; fullscreen and maximized sizes aren't worth restoring
+        let window = frame.info().window_info;
+        if !window.fullscreen && !window.maximized && !window.minimized {
+            self.window_geometry = Some((window.size, window.position));
+        }
+        
         // Auto-refresh data
-        if now.duration_since(self.last_update) >= UPDATE_INTERVAL {
-            self.last_update = now;
-            self.refresh_data();
+        let since_update = SystemTime::now().duration_since(self.last_update_wall).unwrap_or_default();
+        let update_interval = self.update_interval();
+        // A paused refresh leaves long gaps on purpose, so they don't count as waking up
+        let refresh_held = self.refresh_paused || self.edit_mode;
+        let woke = !refresh_held && since_update > update_interval * WAKE_DETECTION_INTERVALS;
+        self.poll_load(ctx);
+        self.poll_sheet_export(ctx);
+        self.poll_category_pdf_export(ctx);
+        self.run_health_check(ctx);
+        self.refresh_background_tabs(ctx);
+        if !refresh_held {
+            if self.receiver.is_none() && (woke || now.duration_since(self.last_update) >= update_interval) {
+                if woke {
+                    app_log::log("System wake detected — forced refresh".to_string());
+                }
+                self.last_update = now;
+                self.last_update_wall = SystemTime::now();
+                self.refresh_data();
+            }
+            // Keep ticking while idle so a long gap really means the system was asleep
+            ctx.request_repaint_after(update_interval.saturating_sub(now.duration_since(self.last_update)));
+        }
+        
+        // Links opened while this instance is running
+        self.open_forwarded_links();
+        
+        // Files dragged onto the window
+        self.handle_file_drop(ctx);
+        
+        // Batch imports load one sheet at a time in the background so progress stays visible
+        self.load_next_import();
+        
+        // Scheduled snapshots
+        if let Some(interval) = self.export_schedule.as_ref().map(|schedule| schedule.interval) {
+            let since_export = self.last_export.map(|exported_at| exported_at.elapsed());
+            if !matches!(since_export, Some(elapsed) if elapsed < interval) {
+                self.run_scheduled_export();
+            }
+            let since_export = self.last_export.map_or(Duration::ZERO, |exported_at| exported_at.elapsed());
+            ctx.request_repaint_after(interval.saturating_sub(since_export));
+        }
+        
+        // Pick up comments posted from other viewers
+        if self.show_comment_panel {
+            let since_poll = self.last_comment_poll.map(|polled_at| polled_at.elapsed());
+            if !matches!(since_poll, Some(elapsed) if elapsed < COMMENT_POLL_INTERVAL) {
+                self.poll_comments();
+            }
+            ctx.request_repaint_after(COMMENT_POLL_INTERVAL);
+        }
+        
+        // Feed the previous frame to the NDI output and capture this one
+        if let Some(output) = &mut self.stream_output {
+            if output.is_running() {
+                if let Some(image) = frame.screenshot() {
+                    output.push_frame(&image);
+                }
+                frame.request_screenshot();
+                ctx.request_repaint_after(Duration::from_secs(1) / stream_output::STREAM_FPS);
+            } else {
+                self.stream_output = None;
+                self.show_status("NDI output stopped: ffmpeg exited (is it built with libndi_newtek?)".to_string());
+            }
+        }
+        
+        // Re-parse after settings that affect loading have changed
+        if self.reload_requested {
+            self.reload_requested = false;
+            self.reload_data();
+        }
+        
+        // Hide the status message once it has been shown long enough
+        if let Some((_, shown_at)) = &self.status_message {
+            let elapsed = shown_at.elapsed();
+            if elapsed >= STATUS_MESSAGE_DURATION {
+                self.status_message = None;
+            } else {
+                ctx.request_repaint_after(STATUS_MESSAGE_DURATION - elapsed);
+            }
+        }
+        
+        // Keep repainting while changed cells are still fading out
+        self.cell_change_times.retain(|_, changed_at| changed_at.elapsed() < CHANGE_FLASH_DURATION);
+        if !self.cell_change_times.is_empty() {
+            ctx.request_repaint_after(Duration::from_millis(16));
         }
         
+        // Fullscreen table: borderless fullscreen window with nothing but the table
+        if ctx.input(|i| i.key_pressed(egui::Key::F11)) {
+            self.toggle_fullscreen_table();
+        }
+        if self.window_fullscreen != self.fullscreen_table {
+            self.window_fullscreen = self.fullscreen_table;
+            frame.set_fullscreen(self.fullscreen_table);
+        }
+        if self.fullscreen_table {
+            self.apply_style(ctx);
+            self.table_only_panel(ctx);
+            self.fullscreen_controls(ctx);
+            self.scroll_to_row = None;
+            return;
+        }
+        
+        // Compact mode: Escape leaves it unless it was forced from the command line
+        if self.compact_mode && !self.compact_locked && ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.compact_mode = false;
+        }
+        if self.compact_mode {
+            self.apply_style(ctx);
+            self.compact_layout(ctx);
+            self.scroll_to_row = None;
+            return;
+        }
+        
+        self.handle_shortcuts(ctx);
+        
         // Apply theme
-        ctx.set_visuals(if self.theme_is_dark {
-            egui::Visuals::dark()
-        } else {
-            egui::Visuals::light()
-        });
-
+        self.apply_style(ctx);
+        
         // Top bar menu
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
-                    if ui.button("Open CSV...").clicked() {
-                        self.open_file_dialog();
+                    if ui.add_enabled(!self.source_locked, egui::Button::new("Open CSV...").shortcut_text("Ctrl+O")).clicked() {
+                        self.request_pin_action(PinAction::OpenFile);
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(!self.source_locked, egui::Button::new("Connect to Google Sheet...")).clicked() {
+                        self.request_pin_action(PinAction::ConnectSheet);
+                        ui.close_menu();
+                    }
+                    ui.add_enabled_ui(!self.source_locked && !self.settings.recent_files.is_empty(), |ui| {
+                        ui.menu_button("Recent Files", |ui| {
+                            let mut clicked = None;
+                            for (index, path) in self.settings.recent_files.iter().enumerate() {
+                                let mut label = egui::RichText::new(path.display().to_string());
+                                if !path.exists() {
+                                    label = label.color(ui.visuals().weak_text_color());
+                                }
+                                if ui.button(label).clicked() {
+                                    clicked = Some(index);
+                                }
+                            }
+                            if let Some(index) = clicked {
+                                self.request_pin_action(PinAction::OpenRecent(index));
+                                ui.close_menu();
+                            }
+                        });
+                    });
+                    if ui.add_enabled(!self.source_locked, egui::Button::new("Open URL...")).clicked() {
+                        self.request_pin_action(PinAction::OpenUrl);
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(!self.source_locked, egui::Button::new("Create Table...")).clicked() {
+                        self.request_pin_action(PinAction::CreateTable);
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(!self.source_locked, egui::Button::new("Import URL List...")).clicked() {
+                        self.request_pin_action(PinAction::ImportUrlList);
+                        ui.close_menu();
+                    }
+                    let can_merge = self.data.is_some() && !self.source_locked;
+                    if ui.add_enabled(can_merge, egui::Button::new("Merge With Another Source...")).clicked() {
+                        self.request_pin_action(PinAction::Merge);
+                        ui.close_menu();
+                    }
+                    if ui.button("CSV Options...").clicked() {
+                        self.temp_csv_options = self.csv_options.clone();
+                        self.temp_comment_char = self.csv_options.comment_char
+                            .map(|c| (c as char).to_string())
+                            .unwrap_or_else(|| "#".to_string());
+                        self.show_csv_options_dialog = true;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.add_enabled(self.data.is_some(), egui::Button::new("Export as CSV...")).clicked() {
+                        self.export_csv();
+                        ui.close_menu();
+                    }
+                    ui.checkbox(&mut self.export_column_stats, "Also Export Column Statistics");
+                    let exporting = self.sheet_export_receiver.is_some();
+                    if ui.add_enabled(self.data.is_some() && !exporting, egui::Button::new("Export to New Google Sheet...")).clicked() {
+                        self.temp_export_title = format!("{} {}", self.source_name(), chrono::Local::now().format("%Y-%m-%d %H:%M"));
+                        self.show_sheet_export_dialog = true;
                         ui.close_menu();
                     }
-                    if ui.button("Connect to Google Sheet...").clicked() {
-                        self.show_cloud_dialog = true;
+                    let has_categories = self.data.as_ref().is_some_and(|data| self.category_column(data).is_some());
+                    let exporting_pdfs = self.category_pdf_receiver.is_some();
+                    if ui.add_enabled(has_categories && !exporting_pdfs, egui::Button::new("Export All Categories as PDF..."))
+                        .on_disabled_hover_text("Needs a category column")
+                        .clicked()
+                    {
+                        self.start_category_pdf_export();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    let duplicate_args = self.duplicate_window_args();
+                    if ui.add_enabled(duplicate_args.is_some(), egui::Button::new("New Window with Same Source"))
+                        .on_disabled_hover_text("Only files, the first sheet of a workbook, Google Sheets and CSV URLs can be opened in a new window")
+                        .clicked() {
+                        if let Some(args) = duplicate_args {
+                            self.open_duplicate_window(args);
+                        }
                         ui.close_menu();
                     }
                     ui.separator();
                     if ui.button("Exit").clicked() {
-                        std::process::exit(0);
+                        // Through the normal shutdown path so on_close_event saves the session
+                        frame.close();
+                    }
+                });
+                ui.menu_button("Edit", |ui| {
+                    if ui.add_enabled(!self.undo_history.is_empty(), egui::Button::new("Undo")).clicked() {
+                        self.undo();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.data.is_some(), egui::Button::new("Copy as HTML")).clicked() {
+                        self.copy_html(ui.visuals());
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    let mut edit_mode = self.edit_mode;
+                    if ui.checkbox(&mut edit_mode, "Edit Mode").clicked() {
+                        if edit_mode {
+                            self.request_pin_action(PinAction::EditMode);
+                        } else {
+                            self.set_edit_mode(false);
+                        }
+                    }
+                });
+                ui.menu_button("Data", |ui| {
+                    if ui.add_enabled(self.data.is_some(), egui::Button::new("Auto-Hide Rows...")).clicked() {
+                        self.temp_auto_hide = self.auto_hide_expression.clone();
+                        self.auto_hide_error = None;
+                        self.show_auto_hide_dialog = true;
+                        ui.close_menu();
+                    }
+                    if ui.checkbox(&mut self.auto_merge_first_col, "Fill Blank First Column From Above")
+                        .on_hover_text("For results that only name the category on the first row of each group")
+                        .changed()
+                    {
+                        self.reload_requested = true;
+                    }
+                    let label = format!("Show {} Hidden Rows", self.hidden_rows.len());
+                    if ui.add_enabled(!self.hidden_rows.is_empty(), egui::Button::new(label)).clicked() {
+                        self.hidden_rows.clear();
+                        ui.close_menu();
                     }
                 });
                 ui.menu_button("View", |ui| {
                     if ui.button(if self.theme_is_dark { "Light Theme" } else { "Dark Theme" }).clicked() {
                         self.theme_is_dark = !self.theme_is_dark;
+                        self.custom_visuals = None;
                         ui.close_menu();
                     }
-                    if ui.button("Refresh Data").clicked() {
+                    if ui.add(egui::Button::new("Refresh Data").shortcut_text("Ctrl+R")).clicked() {
                         self.refresh_data();
                         ui.close_menu();
                     }
+                    if ui.button("Settings...").clicked() {
+                        self.open_settings_dialog();
+                        ui.close_menu();
+                    }
+                    // The checkbox only reports the click; unlocking goes through a confirmation
+                    let mut locked = self.source_locked;
+                    if ui.checkbox(&mut locked, "Lock Data Source").clicked() {
+                        if self.source_locked {
+                            self.show_unlock_confirm = true;
+                        } else {
+                            self.source_locked = true;
+                        }
+                        ui.close_menu();
+                    }
+                    ui.menu_button("Columns", |ui| self.columns_menu(ui));
+                    if ui.add_enabled(self.data.is_some(), egui::Button::new("Reorder Columns by Number...")).clicked() {
+                        let columns = self.data.as_ref().map_or(0, |data| data.headers.len());
+                        self.temp_column_order = (0..columns).collect();
+                        self.temp_column_targets = (1..=columns).collect();
+                        self.show_reorder_dialog = true;
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.data.is_some(), egui::Button::new("Sort by Column Name...")).clicked() {
+                        self.open_sort_dialog();
+                        ui.close_menu();
+                    }
+                    ui.checkbox(&mut self.show_stats_panel, "Column Statistics");
+                    ui.checkbox(&mut self.show_aggregate_sidebar, "Top / Bottom Sidebar");
+                    ui.checkbox(&mut self.show_total_row, "Total Row");
+                    ui.checkbox(&mut self.show_rank_bars, "Show Rank Bars");
+                    ui.checkbox(&mut self.show_dnx_strikethrough, "Show DNS/DNF/DQ Strikethrough");
+                    if ui.checkbox(&mut self.auto_group_prefixes, "Group Numbered Columns (S1, S2, ...)").changed() {
+                        self.column_groups = match (&self.data, self.auto_group_prefixes) {
+                            (Some(data), true) => data.prefix_groups(),
+                            _ => Vec::new(),
+                        };
+                    }
+                    ui.menu_button("Split View", |ui| {
+                        let tables: Vec<(String, TableData)> = self.loaded_tables().into_iter()
+                            .map(|(label, data)| (label, data.clone()))
+                            .collect();
+                        if tables.is_empty() {
+                            ui.label("No data loaded");
+                        }
+                        for (label, data) in tables {
+                            if ui.button(format!("Show {} on the Right", label)).clicked() {
+                                self.split_label = label;
+                                self.split_data = Some(data);
+                                ui.close_menu();
+                            }
+                        }
+                        if ui.add_enabled(self.split_data.is_some(), egui::Button::new("Close Split View")).clicked() {
+                            self.split_data = None;
+                            ui.close_menu();
+                        }
+                    });
+                    if ui.add_enabled(self.data.is_some(), egui::Button::new("Athlete Profile...")).clicked() {
+                        self.show_athlete_profile = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Data Preview...").clicked() {
+                        self.show_preview_dialog = true;
+                        self.update_preview();
+                        ui.close_menu();
+                    }
+                    ui.checkbox(&mut self.show_log_panel, "Log");
+                    ui.checkbox(&mut self.show_comment_panel, "Comments");
+                    if ui.button("Compact Mode").on_hover_text("Only the table and a status line; press Escape to leave").clicked() {
+                        self.compact_mode = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Fullscreen Table (F11)").clicked() {
+                        self.toggle_fullscreen_table();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("Fit to Screen").clicked() {
+                        self.fit_to_screen = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Restore Defaults").clicked() {
+                        self.restore_default_sizes();
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Analyze", |ui| {
+                    if ui.add_enabled(self.data.is_some(), egui::Button::new("Column Distribution...")).clicked() {
+                        self.show_distribution_window = true;
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Tools", |ui| {
+                    if ui.add_enabled(self.data.is_some(), egui::Button::new("Add Annotation...")).clicked() {
+                        self.temp_annotation.text.clear();
+                        self.show_annotation_dialog = true;
+                        ui.close_menu();
+                    }
+                    let label = format!("Clear {} Annotations", self.annotations.len());
+                    if ui.add_enabled(!self.annotations.is_empty(), egui::Button::new(label)).clicked() {
+                        self.annotations.clear();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.data.is_some(), egui::Button::new("Auto-Translate Headers...")).clicked() {
+                        self.start_header_translation();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if self.stream_output.is_some() {
+                        if ui.button("Stop NDI Output").clicked() {
+                            self.stream_output = None;
+                            ui.close_menu();
+                        }
+                    } else if ui.button("Start NDI Output...").clicked() {
+                        self.show_stream_dialog = true;
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Share", |ui| {
+                    if ui.add_enabled(self.data_source.is_some(), egui::Button::new("Show QR Code for Data Source")).clicked() {
+                        self.show_qr_code(ctx);
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Templates", |ui| {
+                    for (index, template) in SPORT_TEMPLATES.iter().enumerate() {
+                        if ui.radio(self.template_index == index, template.name).clicked() {
+                            self.apply_template(index);
+                            ui.close_menu();
+                        }
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.auto_detect_template, "Auto-detect Sport");
+                });
+                ui.menu_button("Settings", |ui| {
+                    if ui.button("Display...").clicked() {
+                        self.show_display_settings = true;
+                        ui.close_menu();
+                    }
+                    ui.checkbox(&mut self.offline_mode, "Offline Mode (serve cached data)");
+                    ui.horizontal(|ui| {
+                        ui.label("Sheet cache:");
+                        let mut seconds = self.sheet_cache_ttl.as_secs();
+                        let response = ui.add(egui::DragValue::new(&mut seconds).clamp_range(0..=3600).suffix(" s"))
+                            .on_hover_text("Reuse Google Sheets responses for this long (0 = always fetch)");
+                        if response.changed() {
+                            self.sheet_cache_ttl = Duration::from_secs(seconds);
+                        }
+                    });
+                    if ui.checkbox(&mut self.accessibility_mode, "Accessibility Mode").changed() {
+                        self.restore_default_sizes();
+                    }
+                    if ui.button("Column Presets...").clicked() {
+                        self.show_presets_dialog = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Result Precision...").clicked() {
+                        self.temp_result_decimals = self.result_decimals.unwrap_or(0);
+                        self.show_precision_dialog = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Event Info...").clicked() {
+                        self.open_event_info_dialog();
+                        ui.close_menu();
+                    }
+                    let register = ui.add_enabled(
+                        url_scheme::LINKS_SUPPORTED,
+                        egui::Button::new(format!("Register {}:// Links", url_scheme::URL_SCHEME)),
+                    ).on_disabled_hover_text("Links are not supported on macOS");
+                    if register.clicked() {
+                        match url_scheme::register_url_scheme() {
+                            Ok(()) => self.show_status("Link handler registered".to_string()),
+                            Err(err) => self.show_status(format!("Could not register links: {}", err)),
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Export Schedule...").clicked() {
+                        self.temp_export_enabled = self.export_schedule.is_some();
+                        self.temp_export_schedule = self.export_schedule.clone().unwrap_or_default();
+                        self.show_export_schedule_dialog = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Table Caption...").clicked() {
+                        self.temp_caption = self.table_caption.clone();
+                        self.show_caption_dialog = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Theme Editor...").clicked() {
+                        if self.custom_visuals.is_none() {
+                            self.custom_visuals = Some(self.base_visuals());
+                        }
+                        self.show_theme_editor = true;
+                        ui.close_menu();
+                    }
                 });
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     ui.label(format!("Score Viewer v{}", VERSION));
+                    if self.pin_locked() {
+                        ui.label("🔒").on_hover_text("Read-only — changing the data requires the PIN");
+                    } else if let Some(until) = self.pin_unlocked_until {
+                        let remaining = until.saturating_duration_since(Instant::now()).as_secs();
+                        ui.label(format!("🔓 {}s", remaining)).on_hover_text("Unlocked with PIN");
+                    }
+                    if self.source_locked {
+                        ui.label("🔒").on_hover_text("Data source is locked");
+                    }
                 });
             });
         });
         
-        // Status bar
-        egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
+        // Status bar, tinted by how long ago data was last loaded successfully
+        let data_age = self.data_age();
+        let mut status_frame = egui::Frame::side_top_panel(&ctx.style());
+        if let Some(age) = data_age.filter(|_| !self.accessibility_mode) {
+            status_frame = status_frame.fill(lerp_color(status_frame.fill, data_age_color(age), 0.35));
+        }
+        if data_age.is_some() {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+        egui::TopBottomPanel::bottom("bottom_panel").frame(status_frame).show(ctx, |ui| {
             ui.horizontal(|ui| {
-                match &self.data_source {
-                    Some(DataSource::Local(path)) => {
-                        ui.label(format!("Local file: {}", path.display()));
-                    },
-                    Some(DataSource::Cloud(url, sheet)) => {
-                        ui.label(format!("Google Sheet: {} ({})", url, sheet));
-                    },
-                    None => {
-                        ui.label("No data source selected");
-                    }
+                if let Some(age) = data_age {
+                    ui.label(format!("Updated {}s ago", age.as_secs()));
+                    ui.separator();
+                }
+                ui.label(self.source_label());
+                let network_source = matches!(self.data_source, Some(DataSource::Cloud(..) | DataSource::Url(_)));
+                if let Some(cached_at) = self.offline_cached_at.filter(|_| network_source) {
+                    let minutes = cached_at.elapsed().map_or(0, |age| age.as_secs() / 60);
+                    ui.colored_label(ui.visuals().warn_fg_color, format!("(Offline — cached {}m ago)", minutes));
+                }
+                if self.health_failures >= HEALTH_CHECK_FAILURE_LIMIT {
+                    ui.separator();
+                    ui.colored_label(ui.visuals().error_fg_color, "⚠ Source unreachable");
                 }
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let (icon, hint) = if self.refresh_paused {
+                        ("▶", "Resume auto-refresh")
+                    } else {
+                        ("⏸", "Pause auto-refresh")
+                    };
+                    if ui.small_button(icon).on_hover_text(hint).clicked() {
+                        self.toggle_refresh_paused();
+                    }
+                    ui.separator();
                     if let Some(data) = &self.data {
-                        ui.label(format!("Rows: {}", data.rows.len()));
+                        let mut rows = self.row_count_label(data);
+                        if !self.selected_rows.is_empty() {
+                            rows += &format!(", {} selected", self.selected_rows.len());
+                        }
+                        ui.label(rows);
+                        ui.separator();
+                        let mut density = format!(
+                            "{} cells | {} kB",
+                            format_thousands(data.headers.len() * data.rows.len()),
+                            format_thousands(self.data_bytes / 1024),
+                        );
+                        if let Some(parse_time) = self.parse_time {
+                            density += &format!(" | loaded in {} ms", parse_time.as_millis());
+                        }
+                        ui.label(density);
+                    }
+                    if self.receiver.is_some() && !self.loading {
+                        ui.separator();
+                        ui.spinner().on_hover_text("Refreshing…");
+                    }
+                    if let Some(label) = &self.last_export_label {
+                        ui.separator();
+                        ui.label(format!("Last export: {}", label));
+                    }
+                    if !self.auto_hidden_rows.is_empty() {
+                        ui.separator();
+                        ui.label(format!("Auto-hidden: {}", self.auto_hidden_rows.len()));
+                    }
+                    if let Some(preset) = self.active_preset.and_then(|index| self.column_presets.get(index)) {
+                        ui.separator();
+                        ui.label(format!("Preset: {}", preset.name));
+                    }
+                    if !self.import_queue.is_empty() {
+                        let loaded = self.import_total - self.import_queue.len();
+                        ui.separator();
+                        ui.label(format!("Loading {} of {} sheets…", loaded + 1, self.import_total));
+                    }
+                    if let Some((message, _)) = &self.status_message {
+                        ui.separator();
+                        ui.strong(message);
                     }
                 });
             });
         });
         
+        // Column statistics panel
+        if self.show_stats_panel {
+            egui::SidePanel::right("stats_panel").show(ctx, |ui| {
+                ui.heading("Column Statistics");
+                ui.separator();
+                match &self.data {
+                    Some(data) => Self::column_statistics_panel(ui, data),
+                    None => {
+                        ui.label("No data loaded.");
+                    }
+                }
+            });
+        }
+        
+        // Tabs for the open data sources, including sheets imported from a URL list
+        if !self.tabs.is_empty() || self.data.is_some() {
+            egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    let mut selected = None;
+                    let mut closed = None;
+                    for (index, tab) in self.tabs.iter().enumerate() {
+                        let label = if tab.failed { format!("⚠ {}", tab.label) } else { tab.label.clone() };
+                        let enabled = tab.data.is_some() && !self.source_locked;
+                        if ui.add_enabled(enabled, egui::SelectableLabel::new(self.active_tab == Some(index), label)).clicked() {
+                            selected = Some(index);
+                        }
+                        let close = ui.add_enabled(!self.source_locked, egui::Button::new("✕").small().frame(false));
+                        if close.on_hover_text("Close tab").clicked() {
+                            closed = Some(index);
+                        }
+                        ui.separator();
+                    }
+                    // The table on screen until another source is opened next to it
+                    if self.active_tab.is_none() && self.data.is_some() {
+                        ui.add(egui::SelectableLabel::new(true, self.source_name()));
+                        ui.separator();
+                    }
+                    ui.add_enabled_ui(!self.source_locked, |ui| {
+                        ui.menu_button("+", |ui| {
+                            let actions = [
+                                ("Open CSV...", PinAction::OpenFile),
+                                ("Connect to Google Sheet...", PinAction::ConnectSheet),
+                                ("Open URL...", PinAction::OpenUrl),
+                            ];
+                            for (label, action) in actions {
+                                if ui.button(label).clicked() {
+                                    self.new_tab_requested = true;
+                                    self.request_pin_action(action);
+                                    ui.close_menu();
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Open another data source in a new tab");
+                    });
+                    if let Some(index) = selected {
+                        self.activate_tab(index);
+                    }
+                    if let Some(index) = closed {
+                        self.close_tab(index);
+                    }
+                });
+            });
+        }
+        
+        // Top / bottom leaderboard sidebar
+        if self.show_aggregate_sidebar {
+            egui::SidePanel::right("aggregate_sidebar").show(ctx, |ui| {
+                ui.heading("Top / Bottom");
+                ui.separator();
+                if let Some(data) = self.data.take() {
+                    self.aggregate_sidebar(ui, &data);
+                    self.data = Some(data);
+                } else {
+                    ui.label("No data loaded.");
+                }
+            });
+        }
+        
+        // Live comment feed
+        if self.show_comment_panel {
+            self.comment_panel(ctx);
+        }
+        
         // Main content area with table
+        if self.split_data.is_some() {
+            self.split_view_panel(ctx);
+        } else {
+            self.split_table_rect = None;
+        }
+        
         egui::CentralPanel::default().show(ctx, |ui| {
-            if let Some(data) = &self.data {
-                self.display_table(ui, data);
+            self.main_table_rect = Some(ui.max_rect());
+            if !self.table_caption.is_empty() {
+                self.caption(ui);
+            }
+            if self.show_search_bar {
+                self.search_bar(ui);
+            }
+            if self.data.is_some() {
+                self.category_chips(ui);
+                self.quick_filter_bar(ui);
+                self.expression_filter_bar(ui);
+            }
+            let shown = ui.ctx().animate_bool_with_time(egui::Id::new("table_fade_in"), !self.loading, LOADING_FADE_SECONDS);
+            if self.loading {
+                let area = ui.available_rect_before_wrap();
+                self.loading_skeleton(ui);
+                let label = egui::Rect::from_center_size(area.center(), egui::vec2(120.0, 32.0));
+                ui.allocate_ui_at_rect(label, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Loading…");
+                    });
+                });
+            } else if let Some(data) = self.data.take() {
+                // Rendering needs &mut self for interaction state, so borrow the data out temporarily
+                self.display_table(ui, &data);
+                self.paint_annotations(ui);
+                self.data = Some(data);
+                self.apply_pending_row_move();
+                self.apply_pending_column_swap();
+                
+                // Fade the freshly loaded table in over the skeleton
+                if shown < 1.0 {
+                    let cover = ui.visuals().panel_fill.linear_multiply(1.0 - shown);
+                    ui.painter().rect_filled(ui.min_rect(), 0.0, cover);
+                }
             } else {
-                ui.centered_and_justified(|ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(ui.available_height() / 2.0 - 30.0);
+                    if let Some(error) = &self.last_error {
+                        let mut dismissed = false;
+                        ui.horizontal(|ui| {
+                            ui.colored_label(ui.visuals().error_fg_color, format!("Could not load data: {}", error));
+                            dismissed = ui.small_button("✕").on_hover_text("Dismiss").clicked();
+                        });
+                        if dismissed {
+                            self.last_error = None;
+                        }
+                    }
                     ui.label("No data loaded. Please select a local file or connect to Google Sheets.");
+                    if ui.add_enabled(!self.source_locked, egui::Button::new("Create Table...")).clicked() {
+                        self.request_pin_action(PinAction::CreateTable);
+                    }
                 });
             }
         });
@@ -145,88 +1402,5176 @@ impl App for ScoreViewer {
                     });
                 });
         }
-    }
-}
-
-impl ScoreViewer {
-    fn refresh_data(&mut self) {
-        match &self.data_source {
-            Some(DataSource::Local(path)) => {
-                if let Ok(data) = load_csv_file(path) {
-                    self.data = Some(data);
-                }
-            },
-            Some(DataSource::Cloud(url, sheet)) => {
-                if let Ok(data) = load_google_sheet(url, sheet) {
-                    self.data = Some(data);
+        
+        // Sheet picker for workbooks with more than one sheet
+        if let Some((path, sheets)) = &self.pending_workbook {
+            let mut open = None;
+            let mut cancel = false;
+            egui::Window::new("Choose Sheet")
+                .fixed_size([300.0, 200.0])
+                .show(ctx, |ui| {
+                    ui.label(path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned()));
+                    ui.separator();
+                    egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                        for (index, sheet) in sheets.iter().enumerate() {
+                            let response = ui.selectable_value(&mut self.temp_sheet_index, index, sheet);
+                            if response.double_clicked() {
+                                open = Some(index);
+                            }
+                        }
+                    });
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("Open").clicked() {
+                            open = Some(self.temp_sheet_index);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            cancel = true;
+                        }
+                    });
+                });
+            if let Some(index) = open {
+                if let Some((path, _)) = self.pending_workbook.take() {
+                    self.open_workbook_sheet(path, index);
+                }
+            } else if cancel {
+                self.pending_workbook = None;
+            }
+        }
+        
+        // CSV URL dialog
+        if self.show_url_dialog {
+            egui::Window::new("Open URL")
+                .fixed_size([400.0, 100.0])
+                .show(ctx, |ui| {
+                    ui.label("CSV URL:");
+                    ui.add(egui::TextEdit::singleline(&mut self.temp_csv_url).hint_text("https://example.com/results.csv"));
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        let url = self.temp_csv_url.trim();
+                        let valid = url.starts_with("http://") || url.starts_with("https://");
+                        if ui.add_enabled(valid, egui::Button::new("Open")).clicked() {
+                            self.open_url();
+                            self.show_url_dialog = false;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.show_url_dialog = false;
+                        }
+                    });
+                });
+        }
+        
+        // Data preview dialog
+        if self.show_preview_dialog {
+            self.data_preview_dialog(ctx);
+        }
+        
+        // CSV options dialog
+        if self.show_csv_options_dialog {
+            egui::Window::new("CSV Options")
+                .resizable(false)
+                .show(ctx, |ui| {
+                    let mut skip_comments = self.temp_csv_options.comment_char.is_some();
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut skip_comments, "Skip lines starting with");
+                        ui.add_enabled(
+                            skip_comments,
+                            egui::TextEdit::singleline(&mut self.temp_comment_char)
+                                .char_limit(1)
+                                .desired_width(20.0),
+                        );
+                    });
+                    if !skip_comments {
+                        self.temp_csv_options.comment_char = None;
+                    } else if let Some(c) = self.temp_comment_char.chars().next().filter(char::is_ascii) {
+                        self.temp_csv_options.comment_char = Some(c as u8);
+                    } else if self.temp_csv_options.comment_char.is_none() {
+                        self.temp_csv_options.comment_char = Some(b'#');
+                    }
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("Apply").clicked() {
+                            self.csv_options = self.temp_csv_options.clone();
+                            self.show_csv_options_dialog = false;
+                            self.reload_data();
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.show_csv_options_dialog = false;
+                        }
+                    });
+                });
+        }
+        
+        // Unlocking the data source needs confirmation
+        if self.show_unlock_confirm {
+            egui::Window::new("Unlock Data Source")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label("This will allow changing the data source — are you sure?");
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("Unlock").clicked() {
+                            self.source_locked = false;
+                            self.show_unlock_confirm = false;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.show_unlock_confirm = false;
+                        }
+                    });
+                });
+        }
+        
+        // PIN prompt for a read-only action
+        if self.pending_pin_action.is_some() {
+            self.pin_prompt(ctx);
+        }
+        
+        // Export schedule dialog
+        if self.show_export_schedule_dialog {
+            self.export_schedule_dialog(ctx);
+        }
+        
+        // QR code window
+        if self.show_qr_window {
+            self.qr_code_window(ctx);
+        }
+        
+        // Auto-hide rows dialog
+        if self.show_auto_hide_dialog {
+            self.auto_hide_dialog(ctx);
+        }
+        
+        // Column reorder dialog
+        if self.show_reorder_dialog {
+            self.reorder_columns_dialog(ctx);
+        }
+        
+        // Sort dialog
+        if self.show_sort_dialog {
+            self.sort_dialog(ctx);
+        }
+        
+        // Column presets dialog
+        if self.show_presets_dialog {
+            self.column_presets_dialog(ctx);
+        }
+        
+        // Result precision dialog
+        if self.show_precision_dialog {
+            egui::Window::new("Result Precision")
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Decimal places:");
+                        ui.add(egui::DragValue::new(&mut self.temp_result_decimals).clamp_range(0..=MAX_RESULT_DECIMALS));
+                    });
+                    ui.label("Detected from the data when a new source is loaded.");
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("Apply").clicked() {
+                            self.result_decimals = Some(self.temp_result_decimals);
+                            self.show_precision_dialog = false;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.show_precision_dialog = false;
+                        }
+                    });
+                });
+        }
+        
+        // Table caption dialog
+        if self.show_caption_dialog {
+            egui::Window::new("Table Caption")
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label("Shown above the table, e.g. \"National Championship — Final Results\":");
+                    ui.add(egui::TextEdit::singleline(&mut self.temp_caption).desired_width(400.0));
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("Apply").clicked() {
+                            self.table_caption = self.temp_caption.trim().to_string();
+                            self.show_caption_dialog = false;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.show_caption_dialog = false;
+                        }
+                    });
+                });
+        }
+        
+        // Cell value history
+        self.cell_history_window(ctx);
+        
+        // Row comparison
+        self.compare_rows_window(ctx);
+        
+        // Inline table editor
+        if self.show_table_editor {
+            self.table_editor(ctx);
+        }
+        
+        // Merge sources dialog
+        if self.show_merge_dialog {
+            self.merge_dialog(ctx);
+        }
+        
+        // Display settings dialog
+        if self.show_display_settings {
+            self.display_settings_dialog(ctx);
+        }
+        
+        // Theme editor
+        if self.show_theme_editor {
+            self.theme_editor_dialog(ctx);
+        }
+        
+        // Confirm a row dragged between the split-view tables
+        if let Some(split_move) = self.pending_split_move {
+            let target = if split_move.to_split { self.split_label.clone() } else { "the main view".to_string() };
+            egui::Window::new("Move Row")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!("Move row to {}?", target));
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("Move").clicked() {
+                            self.apply_split_move(split_move);
+                            self.pending_split_move = None;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.pending_split_move = None;
+                        }
+                    });
+                });
+        }
+        
+        // Athlete profile
+        if self.show_athlete_profile {
+            self.athlete_profile_window(ctx);
+        }
+        
+        // Annotation dialog
+        if self.show_annotation_dialog {
+            self.annotation_dialog(ctx);
+        }
+        
+        // Header translation review
+        if self.show_translation_dialog {
+            self.translation_dialog(ctx);
+        }
+        
+        // Export to a new Google Sheet
+        if self.show_sheet_export_dialog {
+            self.sheet_export_dialog(ctx);
+        }
+        
+        // Per-category PDF export progress
+        if self.category_pdf_receiver.is_some() {
+            egui::Window::new("Exporting PDFs")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(&self.category_pdf_status);
+                    });
+                });
+        }
+        
+        // NDI output dialog
+        if self.show_stream_dialog {
+            self.stream_dialog(ctx);
+        }
+        
+        // Histogram of a numeric column, redrawn from the current data every frame
+        if self.show_distribution_window {
+            self.distribution_window(ctx);
+        }
+        
+        // Strict schema rejection
+        if !self.schema_errors.is_empty() {
+            self.schema_error_dialog(ctx);
+        }
+        
+        // Command palette, drawn last so it sits above the other windows
+        if self.show_command_palette {
+            self.command_palette(ctx);
+        }
+        
+        // Settings dialog
+        if self.show_settings_dialog {
+            self.settings_dialog(ctx);
+        }
+        
+        // Event info for exports
+        if self.show_event_info_dialog {
+            self.event_info_dialog(ctx);
+        }
+        
+        // Log panel
+        if self.show_log_panel {
+            self.log_panel(ctx);
+        }
+        
+        // A "+" whose PIN prompt or dialog was cancelled leaves the tabs as they were
+        if self.new_tab_requested
+            && !self.show_cloud_dialog
+            && !self.show_url_dialog
+            && self.pending_workbook.is_none()
+            && self.pending_pin_action.is_none()
+        {
+            self.new_tab_requested = false;
+        }
+        
+        // A selected row that is filtered out was never drawn; don't jump to it later
+        self.scroll_to_row = None;
+    }
+}
+
+impl ScoreViewer {
+    // Embedding API: nothing in the viewer itself registers actions yet
+    #[allow(dead_code)]
+    pub fn register_row_action(&mut self, label: impl Into<String>, callback: impl Fn(&[Vec<String>]) + Send + 'static) {
+        self.custom_row_actions.push(RowAction {
+            label: label.into(),
+            callback: Box::new(callback),
+        });
+    }
+    
+    fn with_data_source(data_source: DataSource, settings: Settings) -> Self {
+        let mut viewer = Self { settings, ..Self::default() };
+        match &data_source {
+            DataSource::Local(path) | DataSource::Workbook(path, _) => {
+                viewer.file_path = Some(path.clone());
+            },
+            DataSource::Cloud(url, sheet) => {
+                viewer.sheet_url = url.clone();
+                viewer.sheet_name = sheet.clone();
+            },
+            DataSource::Url(url) => viewer.temp_csv_url = url.clone(),
+            DataSource::Inline(_) | DataSource::Merged { .. } => {}
+        }
+        // Files are read before the window opens so its icon can show the table; a network
+        // source loads in the background like any other refresh instead of holding up launch
+        let local = matches!(data_source, DataSource::Local(_) | DataSource::Workbook(..) | DataSource::Inline(_));
+        viewer.data_source = Some(data_source);
+        viewer.refresh_data();
+        if local {
+            viewer.wait_for_load();
+        }
+        viewer
+    }
+    
+    fn refresh_data(&mut self) {
+        // A source still being opened would otherwise finish as a refresh and skip its setup
+        if self.pending_load.as_ref().is_some_and(|pending| pending.replace) {
+            return;
+        }
+        let Some(source) = self.data_source.clone() else {
+            return;
+        };
+        match &source {
+            DataSource::Local(path) | DataSource::Workbook(path, _) => {
+                // Standard input can only be consumed once
+                if path == Path::new("-") && (self.data.is_some() || self.receiver.is_some()) {
+                    return;
+                }
+                
+                // Only re-parse when the file has changed since the last load
+                let modified = file_modified_time(path);
+                if modified.is_some() && modified == self.last_modified {
+                    // Unchanged on disk still means the data is current
+                    self.last_successful_load = Some(Instant::now());
+                    return;
+                }
+                
+                self.start_load(source, modified, false);
+            },
+            DataSource::Inline(_) => {},
+            // Merged sources always reload both sides so neither can go stale
+            DataSource::Cloud(..) | DataSource::Url(_) | DataSource::Merged { .. } => self.start_load(source, None, false),
+        }
+    }
+    
+    // Probe the source every few seconds, independently of refreshes, so an outage shows up
+    // before a refresh fails
+    fn run_health_check(&mut self, ctx: &egui::Context) {
+        if let Some(receiver) = &self.health_receiver {
+            match receiver.try_recv() {
+                Ok(reachable) => {
+                    self.health_receiver = None;
+                    self.health_failures = if reachable { 0 } else { self.health_failures + 1 };
+                    if self.health_failures == HEALTH_CHECK_FAILURE_LIMIT {
+                        app_log::log("Health check: source unreachable".to_string());
+                    }
+                },
+                Err(TryRecvError::Empty) => {
+                    ctx.request_repaint_after(Duration::from_millis(100));
+                    return;
+                },
+                Err(TryRecvError::Disconnected) => self.health_receiver = None,
+            }
+        }
+        
+        let Some(source) = self.data_source.clone() else {
+            return;
+        };
+        let since_check = self.last_health_check.map(|checked_at| checked_at.elapsed());
+        if !matches!(since_check, Some(elapsed) if elapsed < HEALTH_CHECK_INTERVAL) {
+            self.last_health_check = Some(Instant::now());
+            let (sender, receiver) = mpsc::channel();
+            std::thread::spawn(move || {
+                let _ = sender.send(source_reachable(&source));
+            });
+            self.health_receiver = Some(receiver);
+        }
+        ctx.request_repaint_after(HEALTH_CHECK_INTERVAL);
+    }
+    
+    fn header_config(&self) -> HeaderConfig {
+        HeaderConfig {
+            column_overrides: self.column_overrides.clone(),
+            ..self.header_config.clone()
+        }
+    }
+    
+    fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            csv_options: self.csv_options.clone(),
+            header_config: self.header_config(),
+            sheet_cache_ttl: self.sheet_cache_ttl,
+        }
+    }
+    
+    // Load on a background thread; the result is picked up by `poll_load`.
+    // Replaces any load still running, whose result is then dropped.
+    fn start_load(&mut self, source: DataSource, modified: Option<SystemTime>, replace: bool) {
+        let (sender, receiver) = mpsc::channel();
+        let options = self.load_options();
+        let thread_source = source.clone();
+        // A new source must not be compared against the previous one's ETag
+        let mut etag = if replace { None } else { self.sheet_etag.clone() };
+        std::thread::spawn(move || {
+            let result = match &thread_source {
+                DataSource::Cloud(url, sheet) => {
+                    load_google_sheet(url, sheet, &options.header_config, options.sheet_cache_ttl, &mut etag)
+                },
+                _ => load_source(&thread_source, &options).map(FetchResult::Updated),
+            };
+            let result = result.map(|fetched| (fetched, etag)).map_err(|err| err.to_string());
+            // The receiver is gone if another load has replaced this one
+            let _ = sender.send(result);
+        });
+        
+        // Only a new source hides the table; refreshes keep showing the current data
+        self.loading = replace || self.data.is_none();
+        self.receiver = Some(receiver);
+        self.pending_load = Some(PendingLoad { source, started: Instant::now(), modified, replace });
+    }
+    
+    fn poll_load(&mut self, ctx: &egui::Context) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(result) => self.finish_load(result),
+            Err(TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(100)),
+            Err(TryRecvError::Disconnected) => self.finish_load(Err("Loading stopped unexpectedly".to_string())),
+        }
+    }
+    
+    // Block until the running load is done; used before the window exists
+    fn wait_for_load(&mut self) {
+        if let Some(receiver) = &self.receiver {
+            let result = receiver.recv().unwrap_or_else(|_| Err("Loading stopped unexpectedly".to_string()));
+            self.finish_load(result);
+        }
+    }
+    
+    fn finish_load(&mut self, outcome: FetchOutcome) {
+        self.receiver = None;
+        self.loading = false;
+        let Some(pending) = self.pending_load.take() else {
+            return;
+        };
+        self.parse_time = Some(pending.started.elapsed());
+        let (result, etag) = match outcome {
+            Ok((FetchResult::Updated(data), etag)) => (Ok(data), etag),
+            // The sheet hasn't changed: the current data is still up to date
+            Ok((FetchResult::Unchanged, _)) => {
+                self.last_update = Instant::now();
+                self.last_successful_load = Some(Instant::now());
+                self.last_error = None;
+                return;
+            },
+            Err(err) => (Err(err), None),
+        };
+        // Only a failed fetch may fall back to the offline cache, not data that failed validation
+        let fetch_failed = result.is_err();
+        let result = result.and_then(|data| self.check_schema(data, &pending));
+        match &result {
+            Ok(_) => {
+                // Only a sheet that was accepted may be skipped as unchanged next time
+                self.sheet_etag = etag;
+                self.last_successful_load = Some(Instant::now());
+                self.last_error = None;
+                self.health_failures = 0;
+            },
+            Err(err) => {
+                app_log::log(format!("Load failed: {}", err));
+                self.last_error = Some(err.clone());
+            },
+        }
+        
+        let data = match &pending.source {
+            DataSource::Local(path) | DataSource::Workbook(path, _)
+                if pending.replace && result.is_ok() && path != Path::new("-") => {
+                self.last_modified = pending.modified;
+                self.settings.add_recent_file(path.clone());
+                if let Err(err) = self.settings.save() {
+                    app_log::log(format!("Could not save settings: {}", err));
+                }
+                result.ok()
+            },
+            DataSource::Local(_) | DataSource::Workbook(..) | DataSource::Inline(_) => {
+                let data = result.ok();
+                if data.is_some() {
+                    self.last_modified = pending.modified;
+                }
+                data
+            },
+            DataSource::Cloud(..) | DataSource::Url(_) | DataSource::Merged { .. } => match result {
+                Ok(data) => {
+                    self.offline_cache.insert(pending.source.cache_key(), (data.clone(), SystemTime::now()));
+                    self.offline_cached_at = None;
+                    Some(data)
+                },
+                Err(_) if fetch_failed => self.offline_fallback(&pending.source),
+                Err(_) => None,
+            },
+        };
+        match data {
+            Some(data) if pending.replace => {
+                self.column_order.clear();
+                self.set_data(data);
+            },
+            Some(data) => self.apply_refreshed_data(data),
+            None => {}
+        }
+    }
+    
+    // In strict mode, a table whose columns don't match the configured schema is turned into a
+    // load error so the previous table stays on screen. Only opening a source brings up the dialog;
+    // a live source that stays off-schema would otherwise reopen it on every refresh.
+    fn check_schema(&mut self, data: TableData, pending: &PendingLoad) -> LoadResult {
+        let Some(failures) = self.schema_failures(&data) else {
+            return Ok(data);
+        };
+        if pending.replace {
+            self.data_source = self.loaded_source.clone();
+            self.schema_errors = failures;
+        } else {
+            self.show_status(format!("Refresh rejected by strict schema: {}", failures.join("; ")));
+        }
+        Err("the columns don't match the expected schema".to_string())
+    }
+    
+    // What strict schema mode objects to in `data`, or None when it is off or the columns match
+    fn schema_failures(&self, data: &TableData) -> Option<Vec<String>> {
+        if !self.settings.strict_schema || self.settings.expected_columns.is_empty() {
+            return None;
+        }
+        data.validate_schema(&self.settings.expected_columns).err()
+    }
+    
+    fn schema_error_dialog(&mut self, ctx: &egui::Context) {
+        // Dim the rest of the window: nothing else should be used until this is acknowledged
+        ctx.layer_painter(egui::LayerId::new(egui::Order::PanelResizeLine, egui::Id::new("schema_error_backdrop")))
+            .rect_filled(ctx.screen_rect(), 0.0, egui::Color32::from_black_alpha(120));
+        let mut dismissed = false;
+        let response = egui::Window::new("Import Rejected")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("Strict schema mode is on and the loaded data does not have the expected columns:");
+                ui.add_space(4.0);
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for failure in &self.schema_errors {
+                        ui.colored_label(ui.visuals().error_fg_color, format!("• {}", failure));
+                    }
+                });
+                ui.add_space(4.0);
+                ui.label("The previous data is still displayed.");
+                ui.separator();
+                dismissed = ui.button("OK").clicked();
+            });
+        if let Some(response) = response {
+            ctx.move_to_top(response.response.layer_id);
+        }
+        if dismissed {
+            self.schema_errors.clear();
+        }
+    }
+    
+    // The last copy of `source` that loaded, when offline mode allows showing it after a failed fetch
+    fn offline_fallback(&mut self, source: &DataSource) -> Option<TableData> {
+        if !self.offline_mode {
+            return None;
+        }
+        let (data, cached_at) = self.offline_cache.get(&source.cache_key())?;
+        self.offline_cached_at = Some(*cached_at);
+        Some(data.clone())
+    }
+    
+    // Replace the current data, remembering which cells changed so they can be flashed
+    fn apply_refreshed_data(&mut self, mut data: TableData) {
+        self.restore_column_order(&mut data);
+        if let Some(old) = &self.data {
+            self.restore_locked_rows(old, &mut data);
+            let now = Instant::now();
+            for (row_idx, row) in data.rows.iter().enumerate() {
+                let old_row = old.rows.get(row_idx);
+                for (col, cell) in row.iter().enumerate() {
+                    if old_row.and_then(|r| r.get(col)) != Some(cell) {
+                        self.cell_change_times.insert((row_idx, col), now);
+                    }
+                }
+            }
+        }
+        // Snapshots hold the rows from before this refresh; restoring one would undo it too
+        self.undo_history.clear();
+        self.set_data(data);
+    }
+    
+    // Refreshed data arrives in the source's order; put the columns back where the user moved them,
+    // matching by header name. Vanished columns drop out and new ones go at the end.
+    fn restore_column_order(&mut self, data: &mut TableData) {
+        let Some(old) = &self.data else {
+            return;
+        };
+        if self.column_order.is_empty() {
+            return;
+        }
+        let mut used = vec![false; data.headers.len()];
+        let mut order = Vec::with_capacity(data.headers.len());
+        for header in &old.headers {
+            if let Some(col) = (0..data.headers.len()).find(|&col| !used[col] && data.headers[col] == *header) {
+                used[col] = true;
+                order.push(col);
+            }
+        }
+        order.extend((0..data.headers.len()).filter(|&col| !used[col]));
+        data.arrange_columns(&order);
+        self.column_order = order;
+    }
+    
+    // Locked rows keep their previous values instead of taking the live ones
+    fn restore_locked_rows(&self, old: &TableData, data: &mut TableData) {
+        if self.locked_rows.is_empty() || old.headers != data.headers {
+            return;
+        }
+        
+        let previous: HashMap<String, &Vec<String>> = old.rows.iter()
+            .map(|row| (old.row_key(row), row))
+            .filter(|(key, _)| self.locked_rows.contains(key))
+            .collect();
+        for row_idx in 0..data.rows.len() {
+            if let Some(old_row) = previous.get(&data.row_key(&data.rows[row_idx])) {
+                data.rows[row_idx] = (*old_row).clone();
+            }
+        }
+    }
+    
+    // Force a re-parse even if the source looks unchanged
+    fn reload_data(&mut self) {
+        self.last_modified = None;
+        self.sheet_etag = None;
+        self.refresh_data();
+    }
+    
+    fn open_settings_dialog(&mut self) {
+        self.temp_settings = self.settings.clone();
+        self.temp_dnx_patterns = self.settings.dnx_patterns.join(", ");
+        self.temp_expected_columns = self.settings.expected_columns.join(", ");
+        self.show_settings_dialog = true;
+    }
+    
+    fn open_event_info_dialog(&mut self) {
+        self.temp_print_header = self.settings.print_header.clone();
+        self.temp_event_date = self.temp_print_header.event_date.format("%Y-%m-%d").to_string();
+        self.temp_date_format = self.settings.date_display_format.clone();
+        self.show_event_info_dialog = true;
+    }
+    
+    // Actions available right now, mirroring what the menus have enabled
+    fn command_palette_entries(&self) -> Vec<CommandPaletteEntry> {
+        let has_data = self.data.is_some();
+        let unlocked = !self.source_locked;
+        let has_categories = self.data.as_ref().is_some_and(|data| self.category_column(data).is_some());
+        let mut entries = Vec::new();
+        let mut add = |label: &str, enabled: bool, action: fn(&mut ScoreViewer)| {
+            if enabled {
+                entries.push(CommandPaletteEntry { label: label.to_string(), action });
+            }
+        };
+        add("Open CSV", unlocked, |v| v.request_pin_action(PinAction::OpenFile));
+        add("Connect to Google Sheet", unlocked, |v| v.request_pin_action(PinAction::ConnectSheet));
+        add("Open URL", unlocked, |v| v.request_pin_action(PinAction::OpenUrl));
+        add("Create Table", unlocked, |v| v.request_pin_action(PinAction::CreateTable));
+        add("Export as CSV", has_data, |v| v.export_csv());
+        add("Export All Categories as PDF", has_categories && self.category_pdf_receiver.is_none(), |v| v.start_category_pdf_export());
+        add("Undo", !self.undo_history.is_empty(), |v| v.undo());
+        add("Refresh Data", true, |v| v.refresh_data());
+        add(if self.refresh_paused { "Resume Auto-Refresh" } else { "Pause Auto-Refresh" }, true, |v| v.toggle_refresh_paused());
+        add("Toggle Theme", true, |v| {
+            v.theme_is_dark = !v.theme_is_dark;
+            v.custom_visuals = None;
+        });
+        add("Sort by Column Name", has_data, |v| v.open_sort_dialog());
+        add("Reset Column Order", has_data, |v| v.reset_column_order());
+        add("Toggle Column Statistics", true, |v| v.show_stats_panel = !v.show_stats_panel);
+        add("Toggle Total Row", true, |v| v.show_total_row = !v.show_total_row);
+        add("Toggle Rank Bars", true, |v| v.show_rank_bars = !v.show_rank_bars);
+        add("Athlete Profile", has_data, |v| v.show_athlete_profile = true);
+        add("Column Distribution", has_data, |v| v.show_distribution_window = true);
+        add("Data Preview", true, |v| {
+            v.show_preview_dialog = true;
+            v.update_preview();
+        });
+        add("Compact Mode", true, |v| v.compact_mode = true);
+        add("Fullscreen Table", true, |v| v.toggle_fullscreen_table());
+        add("Fit to Screen", true, |v| v.fit_to_screen = true);
+        add("Restore Default Sizes", true, |v| v.restore_default_sizes());
+        add("Settings", true, |v| v.open_settings_dialog());
+        add("Event Info", true, |v| v.open_event_info_dialog());
+        entries
+    }
+    
+    fn command_palette(&mut self, ctx: &egui::Context) {
+        let matcher = SkimMatcherV2::default();
+        let mut entries: Vec<(i64, CommandPaletteEntry)> = self.command_palette_entries().into_iter()
+            .filter_map(|entry| {
+                let score = if self.command_palette_query.is_empty() {
+                    0
+                } else {
+                    matcher.fuzzy_match(&entry.label, &self.command_palette_query)?
+                };
+                Some((score, entry))
+            })
+            .collect();
+        // Stable, so equal scores keep the palette's own order
+        entries.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        
+        // Taken before the text field sees them; Escape is handled with the other dialogs
+        let (up, down, enter) = ctx.input_mut(|i| (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+        ));
+        let last = entries.len().saturating_sub(1);
+        if up {
+            self.command_palette_selected = self.command_palette_selected.saturating_sub(1);
+        }
+        if down {
+            self.command_palette_selected = (self.command_palette_selected + 1).min(last);
+        }
+        self.command_palette_selected = self.command_palette_selected.min(last);
+        
+        // Dim everything behind the palette
+        let screen = ctx.screen_rect();
+        ctx.layer_painter(egui::LayerId::new(egui::Order::PanelResizeLine, egui::Id::new("command_palette_backdrop")))
+            .rect_filled(screen, 0.0, egui::Color32::from_black_alpha(120));
+        
+        let mut activated = enter.then(|| entries.get(self.command_palette_selected).map(|(_, entry)| entry.action)).flatten();
+        let response = egui::Window::new("Command Palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .fixed_size([screen.width() * 0.5, 0.0])
+            .anchor(egui::Align2::CENTER_TOP, [0.0, screen.height() * 0.15])
+            .show(ctx, |ui| {
+                let query = ui.add(egui::TextEdit::singleline(&mut self.command_palette_query)
+                    .hint_text("Type a command…")
+                    .desired_width(f32::INFINITY));
+                query.request_focus();
+                if query.changed() {
+                    self.command_palette_selected = 0;
+                }
+                ui.separator();
+                if entries.is_empty() {
+                    ui.weak("No matching commands");
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(screen.height() * 0.5).show(ui, |ui| {
+                    for (index, (_, entry)) in entries.iter().enumerate() {
+                        let selected = index == self.command_palette_selected;
+                        let item = ui.add_sized(
+                            [ui.available_width(), 0.0],
+                            egui::SelectableLabel::new(selected, &entry.label),
+                        );
+                        if selected && (up || down) {
+                            item.scroll_to_me(None);
+                        }
+                        if item.clicked() {
+                            activated = Some(entry.action);
+                        }
+                    }
+                });
+            });
+        if let Some(response) = response {
+            ctx.move_to_top(response.response.layer_id);
+        }
+        
+        if let Some(action) = activated {
+            self.show_command_palette = false;
+            action(self);
+        }
+    }
+    
+    fn toggle_refresh_paused(&mut self) {
+        self.refresh_paused = !self.refresh_paused;
+        if self.refresh_paused {
+            self.show_status("Auto-refresh paused".to_string());
+        } else {
+            self.restart_refresh_timer();
+            self.show_status("Auto-refresh resumed".to_string());
+        }
+    }
+    
+    // Wait a full interval before the next refresh instead of firing straight away
+    fn restart_refresh_timer(&mut self) {
+        self.last_update = Instant::now();
+        self.last_update_wall = SystemTime::now();
+        for tab in &mut self.tabs {
+            tab.last_update = self.last_update;
+        }
+    }
+    
+    // Auto-refresh holds off while editing, so a refresh can't replace rows that were just moved
+    fn set_edit_mode(&mut self, edit_mode: bool) {
+        if self.edit_mode == edit_mode {
+            return;
+        }
+        self.edit_mode = edit_mode;
+        if edit_mode {
+            self.show_status("Auto-refresh paused while editing".to_string());
+        } else if !self.refresh_paused {
+            self.restart_refresh_timer();
+        }
+    }
+    
+    // None for sources that never refresh
+    fn data_age(&self) -> Option<Duration> {
+        match &self.data_source {
+            Some(DataSource::Inline(_)) | None => None,
+            Some(DataSource::Local(path)) if path == Path::new("-") => None,
+            Some(_) => self.last_successful_load.map(|loaded_at| loaded_at.elapsed()),
+        }
+    }
+    
+    fn source_label(&self) -> String {
+        match &self.data_source {
+            Some(DataSource::Local(path)) if path == Path::new("-") => "Data source: stdin".to_string(),
+            Some(DataSource::Local(path)) => format!("Local file: {}", path.display()),
+            Some(DataSource::Workbook(path, sheet_index)) => format!("Workbook: {} (sheet {})", path.display(), sheet_index + 1),
+            Some(DataSource::Cloud(url, sheet)) => format!("Google Sheet: {} ({})", url, sheet),
+            Some(DataSource::Url(url)) => format!("URL: {}", url),
+            Some(DataSource::Inline(_)) => "Data source: table created in the editor".to_string(),
+            Some(DataSource::Merged { primary, secondary, key_col, .. }) => format!(
+                "Merged: {} + {} (key: {})",
+                primary.cache_key(), secondary.cache_key(), key_col
+            ),
+            None => "No data source selected".to_string(),
+        }
+    }
+    
+    fn row_count_label(&self, data: &TableData) -> String {
+        let shown = data.rows.iter().filter(|row| self.row_passes_filters(data, row)).count();
+        if shown == data.rows.len() {
+            format!("Rows: {}", shown)
+        } else {
+            format!("Rows: {} (of {})", shown, data.rows.len())
+        }
+    }
+    
+    // Only the table and a one-line status bar; dialogs, menus and side panels stay closed
+    fn compact_layout(&mut self, ctx: &egui::Context) {
+        let data_age = self.data_age();
+        if data_age.is_some() {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+        egui::TopBottomPanel::bottom("compact_status").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if let Some(age) = data_age {
+                    ui.label(format!("Updated {}s ago", age.as_secs()));
+                    ui.separator();
+                }
+                ui.label(self.source_label());
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if let Some(data) = &self.data {
+                        ui.label(self.row_count_label(data));
+                    }
+                });
+            });
+        });
+        self.table_only_panel(ctx);
+    }
+    
+    fn table_only_panel(&mut self, ctx: &egui::Context) {
+        self.split_table_rect = None;
+        egui::CentralPanel::default().show(ctx, |ui| {
+            self.main_table_rect = Some(ui.max_rect());
+            if let Some(data) = self.data.take() {
+                self.display_table(ui, &data);
+                self.paint_annotations(ui);
+                self.data = Some(data);
+                self.apply_pending_row_move();
+                self.apply_pending_column_swap();
+            } else if self.loading {
+                ui.centered_and_justified(|ui| ui.spinner());
+            } else {
+                ui.centered_and_justified(|ui| ui.label("No data loaded"));
+            }
+        });
+    }
+    
+    fn toggle_fullscreen_table(&mut self) {
+        self.fullscreen_table = !self.fullscreen_table;
+        self.fullscreen_activity = Instant::now();
+    }
+    
+    // Exit button in the top-right corner, hidden after a few seconds without mouse movement
+    fn fullscreen_controls(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.pointer.delta() != egui::Vec2::ZERO) {
+            self.fullscreen_activity = Instant::now();
+        }
+        let idle = self.fullscreen_activity.elapsed();
+        if idle >= FULLSCREEN_CONTROLS_TIMEOUT {
+            return;
+        }
+        ctx.request_repaint_after(FULLSCREEN_CONTROLS_TIMEOUT - idle);
+        egui::Window::new("fullscreen_controls")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 10.0))
+            .show(ctx, |ui| {
+                if ui.button("Exit Fullscreen").clicked() {
+                    self.toggle_fullscreen_table();
+                }
+            });
+    }
+    
+    // Window geometry, theme and data source for the next launch
+    fn save_session(&mut self) {
+        if let Some((size, position)) = self.window_geometry {
+            self.settings.window_size = Some([size.x, size.y]);
+            self.settings.window_position = position.map(|pos| [pos.x, pos.y]);
+        }
+        self.settings.dark_theme = self.theme_is_dark;
+        self.settings.last_source = self.data_source.as_ref().and_then(SavedSource::from_source);
+        if let Err(err) = self.settings.save() {
+            app_log::log(format!("Could not save session: {}", err));
+        }
+    }
+    
+    fn show_status(&mut self, message: String) {
+        self.status_message = Some((message, Instant::now()));
+    }
+    
+    fn apply_template(&mut self, index: usize) {
+        self.template_index = index;
+        self.header_config = SPORT_TEMPLATES[index].header_config();
+        self.reload_requested = true;
+    }
+    
+    // Runs once per data source, on its first load
+    fn detect_sport_template(&mut self, raw_headers: &[String]) {
+        let key = self.data_source.as_ref().map(DataSource::cache_key);
+        if key.is_none() || key == self.template_detected_for {
+            return;
+        }
+        self.template_detected_for = key;
+        
+        if let Some(index) = templates::detect_template(raw_headers) {
+            self.show_status(format!("Detected sport: {} — applying template", SPORT_TEMPLATES[index].name));
+            if index != self.template_index {
+                self.apply_template(index);
+            }
+        }
+    }
+    
+    // Store newly loaded data and recompute everything derived from its columns
+    fn set_data(&mut self, mut data: TableData) {
+        if self.auto_merge_first_col {
+            data.fill_down(0);
+        }
+        if self.auto_detect_template {
+            self.detect_sport_template(&data.raw_headers);
+        }
+        self.column_priority = data.headers.iter()
+            .map(|header| default_column_priority(header))
+            .collect();
+        self.column_types = infer_column_types(&data);
+        if self.total_row_ops.len() != data.headers.len() {
+            self.total_row_ops = self.column_types.iter()
+                .map(|&numeric| if numeric { TotalOp::Sum } else { TotalOp::None })
+                .collect();
+        }
+        self.record_cell_history(&data);
+        self.detect_result_precision(&data);
+        self.data_bytes = data.rows.iter().flatten().map(String::len).sum();
+        // Placeholder columns that are (almost) always blank are hidden automatically
+        self.auto_hidden_columns = (0..data.headers.len())
+            .filter(|&col| data.empty_ratio(col) >= AUTO_HIDE_EMPTY_RATIO)
+            .map(|col| data.headers[col].clone())
+            .collect();
+        self.column_groups = if self.auto_group_prefixes { data.prefix_groups() } else { Vec::new() };
+        let row_count = data.rows.len();
+        self.selected_rows.retain(|&row_idx| row_idx < row_count);
+        self.data = Some(data);
+        self.loaded_source = self.data_source.clone();
+        self.adopt_into_tab();
+        self.update_expression_filter();
+        self.update_auto_hidden_rows();
+        self.restore_column_config();
+    }
+    
+    // Runs once per data source, on its first load, while the columns are still in source order
+    fn restore_column_config(&mut self) {
+        let key = self.data_source.as_ref().map(DataSource::cache_key);
+        if key.is_none() || key == self.column_config_restored_for {
+            return;
+        }
+        self.column_config_restored_for = key.clone();
+        let (Some(data), Some(config)) = (&self.data, key.and_then(|key| self.settings.column_configs.get(&key))) else {
+            return;
+        };
+        // The source has gained or lost columns since; the saved indices no longer line up
+        if config.visibility.len() != data.headers.len() || config.order.len() != data.headers.len() {
+            return;
+        }
+        let order = config.order.clone();
+        for (header, &visible) in data.headers.iter().zip(&config.visibility) {
+            // Only differences from the default, so auto-hiding keeps working for the rest
+            if visible == self.auto_hidden_columns.contains(header) {
+                self.column_visibility.insert(header.clone(), visible);
+            }
+        }
+        self.apply_column_order(&order);
+    }
+    
+    fn save_column_config(&mut self) {
+        let (Some(source), Some(data)) = (&self.data_source, &self.data) else {
+            return;
+        };
+        if matches!(source, DataSource::Inline(_)) {
+            return;
+        }
+        let count = data.headers.len();
+        let order: Vec<usize> = if self.column_order.len() == count {
+            self.column_order.clone()
+        } else {
+            (0..count).collect()
+        };
+        let mut visibility = vec![true; count];
+        for (position, &col) in order.iter().enumerate() {
+            visibility[col] = self.is_column_visible(&data.headers[position]);
+        }
+        self.settings.column_configs.insert(source.cache_key(), ColumnConfig { visibility, order });
+        if let Err(err) = self.settings.save() {
+            app_log::log(format!("Could not save column config: {}", err));
+        }
+    }
+    
+    fn forget_column_config(&mut self) {
+        let Some(key) = self.data_source.as_ref().map(DataSource::cache_key) else {
+            return;
+        };
+        if self.settings.column_configs.remove(&key).is_some() {
+            if let Err(err) = self.settings.save() {
+                app_log::log(format!("Could not save settings: {}", err));
+            }
+            self.show_status("Column config forgotten for this source".to_string());
+        }
+    }
+    
+    // Runs once per data source, like template detection, so manual changes stick across refreshes
+    fn detect_result_precision(&mut self, data: &TableData) {
+        let key = self.data_source.as_ref().map(DataSource::cache_key);
+        if key == self.precision_detected_for {
+            return;
+        }
+        self.precision_detected_for = key;
+        self.result_decimals = data.headers.iter()
+            .position(|header| header == RESULT_COLUMN)
+            .map(|col| data.max_decimal_places(col).min(MAX_RESULT_DECIMALS));
+    }
+    
+    // Append every cell whose value differs from the last one recorded for it
+    fn record_cell_history(&mut self, data: &TableData) {
+        let now = Instant::now();
+        for row in &data.rows {
+            let row_key = data.row_key(row);
+            for (header, cell) in data.headers.iter().zip(row) {
+                let history = self.cell_history.entry((row_key.clone(), header.clone())).or_default();
+                if history.last().map(|(_, value)| value) != Some(cell) {
+                    history.push((now, cell.clone()));
+                }
+            }
+        }
+    }
+    
+    // Every loaded table with a label: the sheet tabs, using the live data for the active one,
+    // or just the current table when nothing was imported
+    fn loaded_tables(&self) -> Vec<(String, &TableData)> {
+        let mut tables: Vec<(String, &TableData)> = self.tabs.iter()
+            .enumerate()
+            .filter_map(|(index, tab)| {
+                let data = if self.active_tab == Some(index) { self.data.as_ref() } else { tab.data.as_ref() };
+                Some((tab.label.clone(), data?))
+            })
+            .collect();
+        if self.active_tab.is_none() {
+            if let Some(data) = &self.data {
+                tables.push((self.source_name(), data));
+            }
+        }
+        tables
+    }
+    
+    fn athlete_profile_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_athlete_profile;
+        egui::Window::new("Athlete Profile")
+            .open(&mut open)
+            .default_size([600.0, 300.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.athlete_query);
+                });
+                let query = self.athlete_query.trim().to_lowercase();
+                if query.is_empty() {
+                    ui.label("Type the start of a first name, surname or full name.");
+                    return;
+                }
+                
+                // Columns of every sheet with a match, in order of first appearance
+                let mut headers: Vec<&str> = Vec::new();
+                let mut matches: Vec<(String, &TableData, &Vec<String>)> = Vec::new();
+                for (label, data) in self.loaded_tables() {
+                    let name_col = data.headers.iter().position(|header| header == "Name");
+                    let surname_col = data.headers.iter().position(|header| header == "Surname");
+                    if name_col.is_none() && surname_col.is_none() {
+                        continue;
+                    }
+                    let before = matches.len();
+                    for row in &data.rows {
+                        let name = name_col.and_then(|col| row.get(col)).map_or("", |cell| cell.trim()).to_lowercase();
+                        let surname = surname_col.and_then(|col| row.get(col)).map_or("", |cell| cell.trim()).to_lowercase();
+                        let found = name.starts_with(&query)
+                            || surname.starts_with(&query)
+                            || format!("{} {}", name, surname).starts_with(&query)
+                            || format!("{} {}", surname, name).starts_with(&query);
+                        if found {
+                            matches.push((label.clone(), data, row));
+                        }
+                    }
+                    if matches.len() > before {
+                        for header in &data.headers {
+                            if !headers.contains(&header.as_str()) {
+                                headers.push(header);
+                            }
+                        }
+                    }
+                }
+                
+                ui.label(format!("{} appearances", matches.len()));
+                ui.separator();
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::Grid::new("athlete_profile_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Sheet");
+                            for header in &headers {
+                                ui.strong(*header);
+                            }
+                            ui.end_row();
+                            for (label, data, row) in &matches {
+                                ui.label(label);
+                                for header in &headers {
+                                    let cell = data.headers.iter()
+                                        .position(|h| h == header)
+                                        .and_then(|col| row.get(col))
+                                        .map_or("", String::as_str);
+                                    ui.label(cell);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        self.show_athlete_profile = open;
+    }
+    
+    fn cell_history_window(&mut self, ctx: &egui::Context) {
+        let Some(cell) = self.history_cell.clone() else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new(format!("History: {} — {}", cell.0, cell.1))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let Some(history) = self.cell_history.get(&cell) else {
+                    ui.label("No values recorded yet.");
+                    return;
+                };
+                egui::Grid::new("cell_history_grid")
+                    .striped(true)
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.strong("Recorded");
+                        ui.strong("Value");
+                        ui.end_row();
+                        for (recorded_at, value) in history {
+                            ui.label(format!("{}s ago", recorded_at.elapsed().as_secs()));
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                    });
+            });
+        if !open {
+            self.history_cell = None;
+        }
+    }
+    
+    fn compare_rows_window(&mut self, ctx: &egui::Context) {
+        let Some((first_key, second_key)) = self.compared_rows.clone() else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Compare Rows")
+            .open(&mut open)
+            .default_size([500.0, 400.0])
+            .show(ctx, |ui| {
+                let Some(data) = &self.data else {
+                    ui.label("No data loaded.");
+                    return;
+                };
+                // Look the rows up by key so the comparison follows them across refreshes
+                let find = |key: &str| data.rows.iter().find(|row| data.row_key(row) == key);
+                let (Some(first), Some(second)) = (find(&first_key), find(&second_key)) else {
+                    ui.label("One of the rows is no longer in the table.");
+                    return;
+                };
+                
+                let differs = |col: usize| {
+                    first.get(col).map_or("", |cell| cell.trim()) != second.get(col).map_or("", |cell| cell.trim())
+                };
+                let different = (0..data.headers.len()).filter(|&col| differs(col)).count();
+                ui.label(format!("{} identical, {} different columns", data.headers.len() - different, different));
+                ui.separator();
+                
+                let weak = ui.visuals().weak_text_color();
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::Grid::new("compare_rows_grid")
+                        .striped(true)
+                        .num_columns(3)
+                        .show(ui, |ui| {
+                            ui.strong("Column");
+                            ui.strong(&first_key);
+                            ui.strong(&second_key);
+                            ui.end_row();
+                            for (col, header) in data.headers.iter().enumerate() {
+                                let color = if differs(col) { egui::Color32::YELLOW } else { weak };
+                                ui.label(header);
+                                for row in [first, second] {
+                                    let cell = row.get(col).map_or("", String::as_str);
+                                    ui.label(egui::RichText::new(cell).color(color));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        if !open {
+            self.compared_rows = None;
+        }
+    }
+    
+    // Re-evaluate the auto-hide expression against the current data
+    fn update_auto_hidden_rows(&mut self) {
+        self.auto_hidden_rows.clear();
+        let Some(data) = &self.data else {
+            return;
+        };
+        if self.auto_hide_expression.trim().is_empty() {
+            self.auto_hide_filter = None;
+            return;
+        }
+        // Headers can change between loads, so parse again every time
+        self.auto_hide_filter = FilterExpr::parse(&self.auto_hide_expression, &data.headers).ok();
+        if let Some(filter) = &self.auto_hide_filter {
+            self.auto_hidden_rows = data.rows.iter()
+                .filter(|row| filter.matches(row))
+                .map(|row| data.row_key(row))
+                .collect();
+        }
+    }
+    
+    fn auto_hide_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_auto_hide_dialog;
+        let mut close = false;
+        egui::Window::new("Auto-Hide Rows")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Hide every row matching this expression (re-checked after each refresh):");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.temp_auto_hide)
+                        .hint_text("e.g. result < 50 || club == \"DNS\"")
+                        .desired_width(400.0),
+                );
+                if let Some(err) = &self.auto_hide_error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Apply").clicked() {
+                        let headers = self.data.as_ref().map(|data| data.headers.as_slice()).unwrap_or_default();
+                        self.auto_hide_error = if self.temp_auto_hide.trim().is_empty() {
+                            None
+                        } else {
+                            FilterExpr::parse(&self.temp_auto_hide, headers).err()
+                        };
+                        if self.auto_hide_error.is_none() {
+                            self.auto_hide_expression = self.temp_auto_hide.clone();
+                            self.update_auto_hidden_rows();
+                            close = true;
+                        }
+                    }
+                    if ui.button("Clear").clicked() {
+                        self.auto_hide_expression.clear();
+                        self.update_auto_hidden_rows();
+                        close = true;
+                    }
+                });
+            });
+        self.show_auto_hide_dialog = open && !close;
+    }
+    
+    // Re-parse the expression filter against the current headers
+    fn update_expression_filter(&mut self) {
+        let headers = self.data.as_ref().map(|data| data.headers.as_slice()).unwrap_or_default();
+        if self.expression_filter.trim().is_empty() {
+            self.filter_expr = None;
+            self.filter_error = None;
+            return;
+        }
+        
+        match FilterExpr::parse(&self.expression_filter, headers) {
+            Ok(expr) => {
+                self.filter_expr = Some(expr);
+                self.filter_error = None;
+            },
+            Err(err) => {
+                self.filter_expr = None;
+                self.filter_error = Some(err);
+            }
+        }
+    }
+    
+    fn caption(&self, ui: &mut egui::Ui) {
+        egui::Frame::none()
+            .inner_margin(egui::Margin::symmetric(0.0, 6.0))
+            .show(ui, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.label(egui::RichText::new(&self.table_caption).strong().size(self.font_size * 1.5));
+                });
+            });
+    }
+    
+    // The category column under whatever name the active template gives it
+    fn category_column(&self, data: &TableData) -> Option<usize> {
+        let name = self.header_config.replacements.iter()
+            .find(|(original, _)| original == "category")
+            .map_or("category", |(_, replacement)| replacement.as_str());
+        data.headers.iter().position(|header| header.eq_ignore_ascii_case(name))
+    }
+    
+    fn category_chips(&mut self, ui: &mut egui::Ui) {
+        let Some(data) = &self.data else { return };
+        let Some(col) = self.category_column(data) else { return };
+        // In order of first appearance, rebuilt every frame so reloads bring in new categories
+        let mut categories: Vec<&str> = Vec::new();
+        for row in &data.rows {
+            if let Some(category) = row.get(col).map(|cell| cell.trim()).filter(|cell| !cell.is_empty()) {
+                if !categories.contains(&category) {
+                    categories.push(category);
+                }
+            }
+        }
+        if categories.len() < 2 {
+            return;
+        }
+        // Forget categories that disappeared, or they would hide every row
+        self.selected_categories.retain(|selected| categories.contains(&selected.as_str()));
+        let progress = stage_progress(data, col);
+        
+        let mut toggled = None;
+        let mut show_all = false;
+        ui.horizontal_wrapped(|ui| {
+            if ui.selectable_label(self.selected_categories.is_empty(), "All").clicked() {
+                show_all = true;
+            }
+            for category in &categories {
+                if ui.selectable_label(self.selected_categories.contains(*category), *category).clicked() {
+                    toggled = Some(category.to_string());
+                }
+                if let Some(&(complete, total)) = progress.get(category) {
+                    let fraction = complete as f32 / total as f32;
+                    let color = if complete == total {
+                        PROGRESS_COMPLETE_COLOR
+                    } else if fraction >= 0.5 {
+                        PROGRESS_PARTIAL_COLOR
+                    } else {
+                        PROGRESS_BEHIND_COLOR
+                    };
+                    ui.add(
+                        egui::ProgressBar::new(fraction)
+                            .desired_width(PROGRESS_BAR_WIDTH)
+                            .fill(color)
+                            .text(format!("{}/{} complete", complete, total)),
+                    );
+                }
+            }
+        });
+        if show_all {
+            self.selected_categories.clear();
+        }
+        if let Some(category) = toggled {
+            if !self.selected_categories.remove(&category) {
+                self.selected_categories.insert(category);
+            }
+        }
+    }
+    
+    fn quick_filter_bar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter_text)
+                    .id(egui::Id::new("filter_input"))
+                    .hint_text("Show rows containing... (Ctrl+Shift+F)")
+                    .desired_width(250.0),
+            );
+            if !self.filter_text.is_empty() && ui.small_button("✕").on_hover_text("Clear filter").clicked() {
+                self.filter_text.clear();
+            }
+        });
+    }
+    
+    fn expression_filter_bar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Expression Filter:");
+            let changed = ui.scope(|ui| {
+                if self.filter_error.is_some() {
+                    let stroke = egui::Stroke::new(1.5, egui::Color32::RED);
+                    ui.visuals_mut().widgets.inactive.bg_stroke = stroke;
+                    ui.visuals_mut().widgets.hovered.bg_stroke = stroke;
+                    ui.visuals_mut().selection.stroke = stroke;
+                }
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.expression_filter)
+                        .hint_text("e.g. result > 80 && club == \"TeamA\"")
+                        .desired_width(400.0),
+                );
+                match &self.filter_error {
+                    Some(err) => response.on_hover_text(err),
+                    None => response,
+                }.changed()
+            }).inner;
+            
+            if changed {
+                self.update_expression_filter();
+            }
+        });
+    }
+    
+    fn handle_search_shortcuts(&mut self, ctx: &egui::Context) {
+        let (open, next, previous, escape, any_key) = ctx.input(|i| (
+            i.modifiers.command && !i.modifiers.shift && i.key_pressed(egui::Key::F),  // Ctrl+Shift+F focuses the filter instead
+            i.key_pressed(egui::Key::F3) && !i.modifiers.shift,
+            i.key_pressed(egui::Key::F3) && i.modifiers.shift,
+            i.key_pressed(egui::Key::Escape),
+            i.events.iter().any(|event| matches!(event, egui::Event::Key { pressed: true, .. })),
+        ));
+        
+        if open {
+            self.show_search_bar = true;
+            ctx.memory_mut(|m| m.request_focus(egui::Id::new("search_input")));
+            return;
+        }
+        if !self.show_search_bar {
+            // Matches stay highlighted after Escape until the next key press
+            if any_key && !self.search_query.is_empty() {
+                self.search_query.clear();
+                self.search_matches.clear();
+                self.search_focus = None;
+            }
+            return;
+        }
+        if escape {
+            self.show_search_bar = false;
+        } else if next || previous {
+            self.step_search_focus(if next { 1 } else { -1 });
+        }
+    }
+    
+    fn step_search_focus(&mut self, step: isize) {
+        let count = self.search_matches.len() as isize;
+        if count == 0 {
+            return;
+        }
+        let current = self.search_focus.map_or(if step > 0 { -1 } else { 0 }, |focus| focus as isize);
+        self.search_focus = Some((current + step).rem_euclid(count) as usize);
+        self.scroll_to_search_focus = true;
+    }
+    
+    fn search_bar(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::popup(ui.style()).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("🔍");
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.search_query)
+                        .id(egui::Id::new("search_input"))
+                        .hint_text("Search all cells")
+                        .desired_width(250.0),
+                );
+                if response.changed() {
+                    self.search_focus = None;
+                }
+                // Enter behaves like F3 while typing
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    self.step_search_focus(1);
+                    response.request_focus();
+                }
+                match self.search_matches.len() {
+                    1 => ui.label("1 match"),
+                    count => ui.label(format!("{} matches", count)),
+                };
+                if ui.small_button("▲").on_hover_text("Previous (Shift+F3)").clicked() {
+                    self.step_search_focus(-1);
+                }
+                if ui.small_button("▼").on_hover_text("Next (F3)").clicked() {
+                    self.step_search_focus(1);
+                }
+                if ui.small_button("✕").on_hover_text("Close (Esc)").clicked() {
+                    self.show_search_bar = false;
+                }
+            });
+        });
+    }
+    
+    fn is_search_match(&self, cell: &str) -> bool {
+        !self.search_query.is_empty() && cell.to_lowercase().contains(&self.search_query.to_lowercase())
+    }
+    
+    fn import_url_list(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("Text Files", &["txt"])
+            .pick_file() else {
+            return;
+        };
+        let sheets = match read_url_list(&path) {
+            Ok(sheets) => sheets,
+            Err(err) => {
+                self.show_status(format!("Failed to read URL list: {}", err));
+                return;
+            }
+        };
+        
+        // The sheets open next to whatever is already open; the first one is shown once it loads
+        self.park_current_source();
+        self.active_tab = None;
+        let first = self.tabs.len();
+        self.tabs.extend(sheets.into_iter()
+            .enumerate()
+            .map(|(index, (url, sheet))| TabState {
+                label: if sheet.is_empty() { format!("Sheet {}", index + 1) } else { sheet.clone() },
+                source: DataSource::Cloud(url, sheet),
+                data: None,
+                failed: false,
+                last_update: Instant::now(),
+                receiver: None,
+            }));
+        self.import_queue = (first..self.tabs.len()).collect();
+        self.import_total = self.import_queue.len();
+    }
+    
+    // The sheet at the front of the queue stays there until `finish_tab_load` has its result
+    fn load_next_import(&mut self) {
+        let Some(&index) = self.import_queue.front() else {
+            return;
+        };
+        if self.tabs[index].receiver.is_none() {
+            self.start_tab_load(index);
+        }
+    }
+    
+    // Load a tab's source on a background thread; `poll_tab_loads` picks up the result
+    fn start_tab_load(&mut self, index: usize) {
+        let options = self.load_options();
+        let tab = &mut self.tabs[index];
+        tab.last_update = Instant::now();
+        let source = tab.source.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(load_source(&source, &options).map_err(|err| err.to_string()));
+        });
+        tab.receiver = Some(receiver);
+    }
+    
+    fn poll_tab_loads(&mut self, ctx: &egui::Context) {
+        let mut finished = Vec::new();
+        for (index, tab) in self.tabs.iter_mut().enumerate() {
+            let Some(receiver) = &tab.receiver else {
+                continue;
+            };
+            let result = match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => {
+                    ctx.request_repaint_after(Duration::from_millis(100));
+                    continue;
+                },
+                Err(TryRecvError::Disconnected) => Err("Loading stopped unexpectedly".to_string()),
+            };
+            tab.receiver = None;
+            finished.push((index, result));
+        }
+        for (index, result) in finished {
+            self.finish_tab_load(index, result);
+        }
+    }
+    
+    fn finish_tab_load(&mut self, index: usize, result: LoadResult) {
+        let importing = self.import_queue.front() == Some(&index);
+        if importing {
+            self.import_queue.pop_front();
+        }
+        // Same checks as a load into the main view, so a tab can't bring in data it would refuse
+        let fetch_failed = result.is_err();
+        let result = result.and_then(|mut data| {
+            if let Some(failures) = self.schema_failures(&data) {
+                return Err(format!("the columns don't match the expected schema ({})", failures.join("; ")));
+            }
+            if self.auto_merge_first_col {
+                data.fill_down(0);
+            }
+            if let Some(old) = &self.tabs[index].data {
+                self.restore_locked_rows(old, &mut data);
+            }
+            Ok(data)
+        });
+        let network = matches!(self.tabs[index].source, DataSource::Cloud(..) | DataSource::Url(_) | DataSource::Merged { .. });
+        let cache_key = self.tabs[index].source.cache_key();
+        match result {
+            Ok(data) => {
+                if network {
+                    self.offline_cache.insert(cache_key, (data.clone(), SystemTime::now()));
+                }
+                let tab = &mut self.tabs[index];
+                tab.data = Some(data);
+                tab.failed = false;
+            },
+            Err(err) => {
+                let label = self.tabs[index].label.clone();
+                app_log::log(format!("Loading {} failed: {}", label, err));
+                self.tabs[index].failed = true;
+                // A tab that never loaded can still show its last good copy in offline mode
+                if fetch_failed && network && self.offline_mode && self.tabs[index].data.is_none() {
+                    self.tabs[index].data = self.offline_cache.get(&cache_key).map(|(data, _)| data.clone());
+                }
+                if importing {
+                    self.show_status(format!("Could not load {}: {}", label, err));
+                }
+            },
+        }
+        
+        // Show the first imported sheet as soon as it is available
+        if importing && self.active_tab.is_none() && self.tabs[index].data.is_some() {
+            self.activate_tab(index);
+        }
+    }
+    
+    fn activate_tab(&mut self, index: usize) {
+        let Some(data) = self.tabs[index].data.clone() else {
+            return;
+        };
+        // The expected columns may have changed since the tab loaded
+        if let Some(failures) = self.schema_failures(&data) {
+            self.schema_errors = failures;
+            return;
+        }
+        self.store_active_tab();
+        let tab = &mut self.tabs[index];
+        if let DataSource::Cloud(url, sheet) = &tab.source {
+            self.sheet_url = url.clone();
+            self.sheet_name = sheet.clone();
+        }
+        // The tab's own timer carries on; the main refresh takes over from its background one
+        tab.receiver = None;
+        self.last_update = tab.last_update;
+        self.data_source = Some(tab.source.clone());
+        self.active_tab = Some(index);
+        // A refresh still running for the previous tab would land in this one
+        self.receiver = None;
+        self.pending_load = None;
+        self.loading = false;
+        self.last_modified = None;
+        self.sheet_etag = None;
+        self.set_data(data);
+    }
+    
+    // Copy the live table and its timer back into the active tab before it goes to the background
+    fn store_active_tab(&mut self) {
+        let Some(tab) = self.active_tab.and_then(|index| self.tabs.get_mut(index)) else {
+            return;
+        };
+        tab.data = self.data.clone();
+        tab.last_update = self.last_update;
+    }
+    
+    // Before another source is opened in a new tab, make sure the current one keeps a tab
+    fn park_current_source(&mut self) {
+        if self.active_tab.is_some() {
+            self.store_active_tab();
+            return;
+        }
+        let (Some(source), Some(data)) = (self.data_source.clone(), self.data.clone()) else {
+            return;
+        };
+        self.tabs.push(TabState {
+            label: self.source_name(),
+            source,
+            data: Some(data),
+            failed: false,
+            last_update: self.last_update,
+            receiver: None,
+        });
+        self.active_tab = Some(self.tabs.len() - 1);
+    }
+    
+    // Once several sources are open, each newly loaded one gets a tab, or selects the tab it already has
+    fn adopt_into_tab(&mut self) {
+        if self.active_tab.is_some() || self.tabs.is_empty() {
+            return;
+        }
+        let Some(source) = self.data_source.clone() else {
+            return;
+        };
+        let key = source.cache_key();
+        if let Some(index) = self.tabs.iter().position(|tab| tab.source.cache_key() == key) {
+            self.tabs[index].receiver = None;
+            self.active_tab = Some(index);
+            return;
+        }
+        self.tabs.push(TabState {
+            label: self.source_name(),
+            source,
+            data: self.data.clone(),
+            failed: false,
+            last_update: self.last_update,
+            receiver: None,
+        });
+        self.active_tab = Some(self.tabs.len() - 1);
+    }
+    
+    fn close_tab(&mut self, index: usize) {
+        self.tabs.remove(index);
+        self.import_queue = self.import_queue.iter()
+            .filter(|&&queued| queued != index)
+            .map(|&queued| if queued > index { queued - 1 } else { queued })
+            .collect();
+        match self.active_tab {
+            Some(active) if active == index => {
+                self.active_tab = None;
+                // Show the nearest loaded tab instead, or nothing once the last one is closed
+                let next = (0..self.tabs.len())
+                    .filter(|&other| self.tabs[other].data.is_some())
+                    .min_by_key(|&other| other.abs_diff(index));
+                match next {
+                    Some(next) => self.activate_tab(next),
+                    None => {
+                        self.data = None;
+                        self.data_source = None;
+                        self.loaded_source = None;
+                        self.receiver = None;
+                        self.pending_load = None;
+                        self.loading = false;
+                    },
+                }
+            },
+            Some(active) if active > index => self.active_tab = Some(active - 1),
+            _ => {}
+        }
+    }
+    
+    // Inactive tabs keep refreshing on their own timers, loading on background threads
+    fn refresh_background_tabs(&mut self, ctx: &egui::Context) {
+        self.poll_tab_loads(ctx);
+        let update_interval = self.update_interval();
+        let mut due = Vec::new();
+        for (index, tab) in self.tabs.iter().enumerate() {
+            let static_source = match &tab.source {
+                DataSource::Inline(_) => true,
+                DataSource::Local(path) => path == Path::new("-"),
+                _ => false,
+            };
+            if self.refresh_paused
+                || self.active_tab == Some(index)
+                || self.import_queue.contains(&index)
+                || tab.receiver.is_some()
+                || static_source
+                || tab.last_update.elapsed() < update_interval
+            {
+                continue;
+            }
+            due.push(index);
+        }
+        for index in due {
+            self.start_tab_load(index);
+        }
+    }
+    
+    fn open_table_editor(&mut self) {
+        if self.editor_cells.is_empty() {
+            self.editor_cells = vec![vec![String::new(); 3]; 2];
+        }
+        self.show_table_editor = true;
+    }
+    
+    // Tab moves between cells through egui's normal focus order; Enter in a cell adds a row
+    fn table_editor(&mut self, ctx: &egui::Context) {
+        if let Some(id) = self.pending_editor_focus.take() {
+            ctx.memory_mut(|m| m.request_focus(id));
+        }
+        let mut open = self.show_table_editor;
+        let mut use_data = false;
+        egui::Window::new("Create Table")
+            .open(&mut open)
+            .default_size([600.0, 400.0])
+            .show(ctx, |ui| {
+                ui.label("Type the column headers into the first row.");
+                ui.horizontal(|ui| {
+                    if ui.button("+ Column").clicked() {
+                        for row in &mut self.editor_cells {
+                            row.push(String::new());
+                        }
+                    }
+                    if ui.button("+ Row").clicked() {
+                        self.add_editor_row();
+                    }
+                });
+                ui.separator();
+                
+                let mut add_row = false;
+                egui::ScrollArea::both().max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("table_editor_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (row_idx, row) in self.editor_cells.iter_mut().enumerate() {
+                                for (col, cell) in row.iter_mut().enumerate() {
+                                    let mut edit = egui::TextEdit::singleline(cell)
+                                        .id(egui::Id::new(("editor_cell", row_idx, col)))
+                                        .desired_width(100.0);
+                                    if row_idx == 0 {
+                                        edit = edit.hint_text(format!("Column {}", col + 1)).font(egui::TextStyle::Heading);
+                                    }
+                                    let response = ui.add(edit);
+                                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                        add_row = true;
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+                if add_row {
+                    self.add_editor_row();
+                }
+                
+                ui.separator();
+                let has_headers = self.editor_cells.first()
+                    .is_some_and(|headers| headers.iter().any(|header| !header.trim().is_empty()));
+                if ui.add_enabled(has_headers, egui::Button::new("Use This Data")).clicked() {
+                    use_data = true;
+                }
+            });
+        self.show_table_editor = open && !use_data;
+        
+        if use_data {
+            let headers: Vec<String> = self.editor_cells[0].iter().map(|header| header.trim().to_string()).collect();
+            let rows: Vec<Vec<String>> = self.editor_cells[1..].iter()
+                .filter(|row| row.iter().any(|cell| !cell.trim().is_empty()))
+                .cloned()
+                .collect();
+            let data = TableData { raw_headers: headers.clone(), headers, rows, ..TableData::empty() };
+            self.data_source = Some(DataSource::Inline(data.clone()));
+            self.active_tab = None;
+            self.parse_time = None;
+            self.set_data(data);
+        }
+    }
+    
+    fn add_editor_row(&mut self) {
+        let columns = self.editor_cells.first().map_or(0, Vec::len);
+        self.editor_cells.push(vec![String::new(); columns]);
+        let new_row = self.editor_cells.len() - 1;
+        self.pending_editor_focus = Some(egui::Id::new(("editor_cell", new_row, 0usize)));
+    }
+    
+    fn export_schedule_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_export_schedule_dialog;
+        let mut close = false;
+        egui::Window::new("Export Schedule")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut self.temp_export_enabled, "Export a snapshot of the displayed table periodically");
+                ui.add_enabled_ui(self.temp_export_enabled, |ui| {
+                    let schedule = &mut self.temp_export_schedule;
+                    egui::Grid::new("export_schedule_grid").num_columns(2).show(ui, |ui| {
+                        ui.label("Folder:");
+                        ui.horizontal(|ui| {
+                            ui.label(schedule.output_dir.display().to_string());
+                            if ui.button("Browse...").clicked() {
+                                if let Some(dir) = FileDialog::new().pick_folder() {
+                                    schedule.output_dir = dir;
+                                }
+                            }
+                        });
+                        ui.end_row();
+                        
+                        ui.label("Format:");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut schedule.format, ExportFormat::Csv, "CSV");
+                            ui.radio_value(&mut schedule.format, ExportFormat::Json, "JSON");
+                        });
+                        ui.end_row();
+                        
+                        ui.label("Every:");
+                        let mut minutes = schedule.interval.as_secs() / 60;
+                        if ui.add(egui::DragValue::new(&mut minutes).clamp_range(1..=1440).suffix(" min")).changed() {
+                            schedule.interval = Duration::from_secs(minutes * 60);
+                        }
+                        ui.end_row();
+                        
+                        ui.label("File name:");
+                        ui.text_edit_singleline(&mut schedule.filename_template)
+                            .on_hover_text("Placeholders: {date}, {time}, {source_name}");
+                        ui.end_row();
+                    });
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let valid = !self.temp_export_enabled || self.temp_export_schedule.output_dir.is_dir();
+                    if ui.add_enabled(valid, egui::Button::new("Apply")).clicked() {
+                        self.export_schedule = self.temp_export_enabled.then(|| self.temp_export_schedule.clone());
+                        self.last_export = None;
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        self.show_export_schedule_dialog = open && !close;
+    }
+    
+    // Rows as displayed: filtered, sorted, limited to visible columns
+    fn display_data(&self, data: &TableData) -> (Vec<String>, Vec<Vec<String>>) {
+        let columns: Vec<usize> = (0..data.headers.len())
+            .filter(|&col| self.is_column_visible(&data.headers[col]))
+            .collect();
+        let headers = columns.iter().map(|&col| data.headers[col].clone()).collect();
+        let rows = self.display_rows(data).into_iter()
+            .map(|row_idx| columns.iter()
+                .map(|&col| data.rows[row_idx].get(col).cloned().unwrap_or_default())
+                .collect())
+            .collect();
+        (headers, rows)
+    }
+    
+    // Save the displayed table, plus {name}_stats.csv next to it when column statistics are enabled
+    fn export_csv(&mut self) {
+        let Some(data) = &self.data else {
+            return;
+        };
+        let Some(path) = FileDialog::new()
+            .add_filter("CSV Files", &["csv"])
+            .set_file_name(&format!("{}.csv", self.source_name()))
+            .save_file() else {
+            return;
+        };
+        
+        // Filtered, sorted and with hidden columns left out, in the delimiter the source used
+        let delimiter = data.delimiter;
+        let (headers, rows) = self.display_data(data);
+        let row_count = rows.len();
+        let result = std::fs::File::create(&path)
+            .map_err(Into::into)
+            .and_then(|file| write_csv(file, delimiter, &headers, &rows));
+        if let Err(err) = result {
+            self.show_status(format!("Export failed: {}", err));
+            return;
+        }
+        
+        if self.export_column_stats {
+            let stem = path.file_stem().map_or("export".into(), |stem| stem.to_string_lossy());
+            let stats_path = path.with_file_name(format!("{}_stats.csv", stem));
+            let exported = TableData { raw_headers: headers.clone(), headers, rows, ..TableData::empty() };
+            let result = std::fs::File::create(&stats_path)
+                .map_err(Into::into)
+                .and_then(|file| write_column_statistics(file, &exported));
+            if let Err(err) = result {
+                self.show_status(format!("Exported {} rows, but the statistics failed: {}", row_count, err));
+                return;
+            }
+        }
+        self.show_status(format!("Exported {} rows to {}", row_count, path.display()));
+    }
+    
+    // arboard can't put RTF on the clipboard, so this offers HTML (which Word and LibreOffice
+    // paste as a formatted table) alongside a tab-separated plain-text version
+    fn copy_html(&mut self, visuals: &egui::Visuals) {
+        let Some(data) = &self.data else { return };
+        let (headers, rows) = self.display_data(data);
+        let style = rich_text::TableStyle {
+            font_size: self.font_size,
+            text: visuals.text_color(),
+            background: visuals.panel_fill,
+            stripe: visuals.faint_bg_color,
+        };
+        let html = format!(
+            "{}{}{}",
+            rich_text::print_header_html(&self.settings.print_header, &self.settings.date_display_format),
+            rich_text::table_html(&headers, &rows, &style),
+            rich_text::print_footer_html(&self.settings.print_header),
+        );
+        let plain = rich_text::table_plain_text(&headers, &rows);
+        let result = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set().html(html, Some(plain)));
+        match result {
+            Ok(()) => self.show_status(format!("Copied {} rows as HTML", rows.len())),
+            Err(err) => self.show_status(format!("Could not copy: {}", err)),
+        }
+    }
+    
+    fn sheet_export_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_sheet_export_dialog;
+        let mut close = false;
+        egui::Window::new("Export to New Google Sheet")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("sheet_export_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Title:");
+                    ui.text_edit_singleline(&mut self.temp_export_title);
+                    ui.end_row();
+                    ui.label("Access token:");
+                    ui.add(egui::TextEdit::singleline(&mut self.google_access_token).password(true))
+                        .on_hover_text("OAuth2 token with the spreadsheets scope");
+                    ui.end_row();
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let ready = !self.temp_export_title.trim().is_empty() && !self.google_access_token.trim().is_empty();
+                    if ui.add_enabled(ready, egui::Button::new("Create")).clicked() {
+                        self.start_sheet_export();
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        self.show_sheet_export_dialog = open && !close;
+    }
+    
+    // Upload the visible, filtered and sorted table on a background thread
+    fn start_sheet_export(&mut self) {
+        let Some(data) = &self.data else { return };
+        let (headers, rows) = self.display_data(data);
+        let token = self.google_access_token.trim().to_string();
+        let title = self.temp_export_title.trim().to_string();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = create_google_sheet(&token, &title, &headers, &rows).map_err(|err| err.to_string());
+            let _ = sender.send(result);
+        });
+        self.sheet_export_receiver = Some(receiver);
+        self.show_status("Creating Google Sheet…".to_string());
+    }
+    
+    fn poll_sheet_export(&mut self, ctx: &egui::Context) {
+        let Some(receiver) = &self.sheet_export_receiver else {
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => {
+                ctx.request_repaint_after(Duration::from_millis(100));
+                return;
+            },
+            Err(TryRecvError::Disconnected) => Err("Export stopped unexpectedly".to_string()),
+        };
+        self.sheet_export_receiver = None;
+        match result {
+            Ok(url) => {
+                ctx.output_mut(|o| o.copied_text = url.clone());
+                self.show_status(format!("Created: {}", url));
+            },
+            Err(err) => self.show_status(format!("Could not create Google Sheet: {}", err)),
+        }
+    }
+    
+    // One PDF per category of the displayed table, written to a chosen folder on a background thread
+    fn start_category_pdf_export(&mut self) {
+        let Some(data) = &self.data else { return };
+        let Some(category_header) = self.category_column(data).map(|col| data.headers[col].clone()) else {
+            return;
+        };
+        let (headers, rows) = self.display_data(data);
+        let Some(category_col) = headers.iter().position(|header| *header == category_header) else {
+            self.show_status(format!("Show the {} column to export by category", category_header));
+            return;
+        };
+        let Some(dir) = FileDialog::new().pick_folder() else {
+            return;
+        };
+        
+        let table = TableData { headers, rows, ..data.clone() };
+        let print_header = self.settings.print_header.clone();
+        let date_format = self.settings.date_display_format.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let categories = table.split_by_column(category_col);
+            let total = categories.len();
+            for (index, (category, sub_table)) in categories.iter().enumerate() {
+                let _ = sender.send(CategoryPdfProgress::Exporting { current: index + 1, total, category: category.clone() });
+                let name = if category.is_empty() { "Uncategorized" } else { category.as_str() };
+                // Keep the category readable in the file name, only replacing what file systems reject
+                let file_name: String = name.chars()
+                    .map(|c| if c.is_control() || "/\\:*?\"<>|".contains(c) { '_' } else { c })
+                    .collect();
+                let path = dir.join(format!("{}.pdf", file_name));
+                let result = pdf_export::write_table_pdf(&path, name, &sub_table.headers, &sub_table.rows, &print_header, &date_format);
+                if let Err(err) = result {
+                    let _ = sender.send(CategoryPdfProgress::Finished(Err(format!("{}: {}", path.display(), err))));
+                    return;
+                }
+            }
+            let _ = sender.send(CategoryPdfProgress::Finished(Ok(total)));
+        });
+        self.category_pdf_receiver = Some(receiver);
+        self.category_pdf_status = "Splitting by category…".to_string();
+    }
+    
+    fn poll_category_pdf_export(&mut self, ctx: &egui::Context) {
+        let Some(receiver) = &self.category_pdf_receiver else {
+            return;
+        };
+        loop {
+            let result = match receiver.try_recv() {
+                Ok(CategoryPdfProgress::Exporting { current, total, category }) => {
+                    self.category_pdf_status = format!("Exporting {} of {}: {}…", current, total, category);
+                    continue;
+                },
+                Ok(CategoryPdfProgress::Finished(result)) => result,
+                Err(TryRecvError::Empty) => {
+                    ctx.request_repaint_after(Duration::from_millis(100));
+                    return;
+                },
+                Err(TryRecvError::Disconnected) => Err("Export stopped unexpectedly".to_string()),
+            };
+            self.category_pdf_receiver = None;
+            match result {
+                Ok(count) => self.show_status(format!("Exported {} category PDFs", count)),
+                Err(err) => {
+                    app_log::log(format!("Category PDF export failed: {}", err));
+                    self.show_status(format!("PDF export failed: {}", err));
+                },
+            }
+            return;
+        }
+    }
+    
+    fn run_scheduled_export(&mut self) {
+        self.last_export = Some(Instant::now());
+        let (Some(schedule), Some(data)) = (&self.export_schedule, &self.data) else {
+            return;
+        };
+        
+        let now = chrono::Local::now();
+        let file_name = schedule.filename_template
+            .replace("{date}", &now.format("%Y-%m-%d").to_string())
+            .replace("{time}", &now.format("%H-%M-%S").to_string())
+            .replace("{source_name}", &self.source_name());
+        let path = schedule.output_dir.join(format!("{}.{}", file_name, schedule.format.extension()));
+        
+        let (headers, rows) = self.display_data(data);
+        let result = std::fs::File::create(&path)
+            .map_err(Into::into)
+            .and_then(|file| write_table(file, schedule.format, data.delimiter, &headers, &rows));
+        match result {
+            Ok(()) => self.last_export_label = Some(now.format("%H:%M:%S").to_string()),
+            Err(err) => self.show_status(format!("Scheduled export failed: {}", err)),
+        }
+    }
+    
+    // Short name for the current source, safe to use in file names
+    fn source_name(&self) -> String {
+        let name = match &self.data_source {
+            Some(DataSource::Local(path) | DataSource::Workbook(path, _)) => path.file_stem()
+                .map_or("stdin".to_string(), |stem| stem.to_string_lossy().into_owned()),
+            Some(DataSource::Cloud(_, sheet)) if !sheet.is_empty() => sheet.clone(),
+            Some(DataSource::Cloud(..)) => "sheet".to_string(),
+            Some(DataSource::Url(url)) => url.split(['?', '#']).next()
+                .and_then(|path| Path::new(path.trim_end_matches('/')).file_stem())
+                .map_or("url".to_string(), |stem| stem.to_string_lossy().into_owned()),
+            Some(DataSource::Inline(_)) => "table".to_string(),
+            Some(DataSource::Merged { .. }) => "merged".to_string(),
+            None => "export".to_string(),
+        };
+        name.chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect()
+    }
+    
+    fn show_qr_code(&mut self, ctx: &egui::Context) {
+        self.qr_code = match &self.data_source {
+            Some(DataSource::Cloud(url, _) | DataSource::Url(url)) => match qr_code_image(url) {
+                Ok(image) => Some((ctx.load_texture("data_source_qr", image, egui::TextureOptions::NEAREST), url.clone())),
+                Err(err) => {
+                    self.show_status(format!("Could not create QR code: {}", err));
+                    return;
+                }
+            },
+            _ => None,
+        };
+        self.show_qr_window = true;
+    }
+    
+    fn qr_code_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_qr_window;
+        egui::Window::new("Scan to View Results")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| match &self.qr_code {
+                Some((texture, url)) => {
+                    ui.vertical_centered(|ui| {
+                        ui.image(texture, egui::vec2(QR_MIN_SIZE, QR_MIN_SIZE));
+                        ui.label(url);
+                    });
+                },
+                None => {
+                    ui.label("Local files can't be shared as QR codes.");
+                    ui.label("Publish the results to a Google Sheet to share them with phones.");
+                }
+            });
+        self.show_qr_window = open;
+    }
+    
+    // Read-only mode: a PIN is configured and hasn't been entered recently
+    fn pin_locked(&self) -> bool {
+        self.readonly_pin.is_some() && self.pin_unlocked_until.is_none()
+    }
+    
+    fn request_pin_action(&mut self, action: PinAction) {
+        if self.pin_locked() {
+            self.temp_pin.clear();
+            self.pending_pin_action = Some(action);
+        } else {
+            self.run_pin_action(action);
+        }
+    }
+    
+    fn run_pin_action(&mut self, action: PinAction) {
+        match action {
+            PinAction::OpenFile => self.open_file_dialog(),
+            PinAction::OpenRecent(index) => self.open_recent_file(index),
+            PinAction::ConnectSheet => self.show_cloud_dialog = true,
+            PinAction::OpenUrl => self.show_url_dialog = true,
+            PinAction::CreateTable => self.open_table_editor(),
+            PinAction::ImportUrlList => self.import_url_list(),
+            PinAction::Merge => self.show_merge_dialog = true,
+            PinAction::EditMode => self.set_edit_mode(true),
+        }
+    }
+    
+    fn pin_prompt(&mut self, ctx: &egui::Context) {
+        let mut submitted = false;
+        let mut cancelled = false;
+        egui::Window::new("🔒 Enter PIN")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("The viewer is read-only. Enter the PIN to unlock it for a minute.");
+                let response = ui.add(egui::TextEdit::singleline(&mut self.temp_pin).password(true));
+                response.request_focus();
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    submitted = true;
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    submitted |= ui.button("Unlock").clicked();
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+        
+        if submitted {
+            if self.readonly_pin.as_deref() == Some(self.temp_pin.as_str()) {
+                self.pin_unlocked_until = Some(Instant::now() + PIN_UNLOCK_DURATION);
+                if let Some(action) = self.pending_pin_action.take() {
+                    self.run_pin_action(action);
+                }
+            } else {
+                self.show_status("Wrong PIN".to_string());
+            }
+            self.temp_pin.clear();
+        } else if cancelled {
+            self.pending_pin_action = None;
+        }
+    }
+    
+    fn open_forwarded_links(&mut self) {
+        let Some(receiver) = &self.forwarded_links else {
+            return;
+        };
+        let links: Vec<String> = receiver.try_iter().collect();
+        for link in links {
+            if self.source_locked || self.pin_locked() {
+                self.show_status("Data source is locked — ignoring opened link".to_string());
+                continue;
+            }
+            if let Some(source) = url_scheme::parse_link(&link) {
+                self.open_source(source);
+            }
+        }
+    }
+    
+    // A new source, like the File menu opens: not a refresh of whatever was shown before
+    fn open_source(&mut self, source: DataSource) {
+        let mut modified = None;
+        match &source {
+            DataSource::Local(path) | DataSource::Workbook(path, _) => {
+                self.file_path = Some(path.clone());
+                modified = file_modified_time(path);
+            },
+            DataSource::Cloud(url, sheet) => {
+                self.sheet_url = url.clone();
+                self.sheet_name = sheet.clone();
+            },
+            DataSource::Url(url) => self.temp_csv_url = url.clone(),
+            DataSource::Inline(_) | DataSource::Merged { .. } => {}
+        }
+        self.start_new_source(source, modified);
+    }
+    
+    // Every way of opening a source ends here, with the current table still on screen
+    fn start_new_source(&mut self, source: DataSource, modified: Option<SystemTime>) {
+        if std::mem::take(&mut self.new_tab_requested) {
+            self.park_current_source();
+        }
+        self.data_source = Some(source.clone());
+        self.active_tab = None;
+        self.start_load(source, modified, true);
+    }
+    
+    fn open_file_dialog(&mut self) {
+        if let Some(path) = FileDialog::new()
+            .add_filter("Delimited Text", &["csv", "tsv", "txt"])
+            .add_filter("Excel Workbook", &["xlsx"])
+            .pick_file() {
+            
+            self.open_local_file(path);
+        }
+    }
+    
+    // Added to the recent files by `finish_load` once it has loaded.
+    // Workbooks with several sheets ask which one to show first.
+    fn open_local_file(&mut self, path: PathBuf) {
+        if is_xlsx_path(&path) {
+            match xlsx_sheet_names(&path) {
+                Ok(sheets) if sheets.len() > 1 => {
+                    self.temp_sheet_index = 0;
+                    self.pending_workbook = Some((path, sheets));
+                },
+                Ok(_) => self.open_workbook_sheet(path, 0),
+                Err(err) => self.show_status(format!("Could not open workbook: {}", err)),
+            }
+            return;
+        }
+        self.file_path = Some(path.clone());
+        let modified = file_modified_time(&path);
+        self.start_new_source(DataSource::Local(path), modified);
+    }
+    
+    fn open_workbook_sheet(&mut self, path: PathBuf, sheet_index: usize) {
+        self.file_path = Some(path.clone());
+        let modified = file_modified_time(&path);
+        self.start_new_source(DataSource::Workbook(path, sheet_index), modified);
+    }
+    
+    // Open the first dropped delimited text file or workbook, with a "Drop file here" overlay while hovering
+    fn handle_file_drop(&mut self, ctx: &egui::Context) {
+        let hovering = ctx.input(|i| !i.raw.hovered_files.is_empty());
+        if hovering {
+            let screen = ctx.screen_rect();
+            let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("file_drop_overlay")));
+            painter.rect_filled(screen, 0.0, egui::Color32::from_black_alpha(160));
+            painter.text(
+                screen.center(),
+                egui::Align2::CENTER_CENTER,
+                "Drop file here",
+                egui::FontId::proportional(32.0),
+                egui::Color32::WHITE,
+            );
+        }
+        
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        let path = dropped.into_iter()
+            .filter_map(|file| file.path)
+            .find(|path| {
+                let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+                ["csv", "tsv", "txt", "xlsx"].iter().any(|allowed| extension.eq_ignore_ascii_case(allowed))
+            });
+        let Some(path) = path else { return };
+        if self.source_locked || self.pin_locked() {
+            self.show_status("The data source is locked; unlock it to open dropped files".to_string());
+            return;
+        }
+        self.open_local_file(path);
+    }
+    
+    // Files that have gone missing are dropped from the list instead
+    fn open_recent_file(&mut self, index: usize) {
+        let Some(path) = self.settings.recent_files.get(index).cloned() else { return };
+        if path.exists() {
+            self.open_local_file(path);
+            return;
+        }
+        self.settings.recent_files.remove(index);
+        self.show_status(format!("{} no longer exists", path.display()));
+        if let Err(err) = self.settings.save() {
+            app_log::log(format!("Could not save settings: {}", err));
+        }
+    }
+    
+    fn connect_to_sheet(&mut self) {
+        if !self.temp_url.is_empty() {
+            self.sheet_url = self.temp_url.clone();
+            self.sheet_name = self.temp_sheet.clone();
+            let source = DataSource::Cloud(self.sheet_url.clone(), self.sheet_name.clone());
+            self.start_new_source(source, None);
+        }
+    }
+    
+    fn open_url(&mut self) {
+        let source = DataSource::Url(self.temp_csv_url.trim().to_string());
+        self.start_new_source(source, None);
+    }
+    
+    fn merge_dialog(&mut self, ctx: &egui::Context) {
+        let headers = self.data.as_ref().map(|data| data.headers.clone()).unwrap_or_default();
+        if !headers.contains(&self.merge_key_col) {
+            self.merge_key_col = headers.first().cloned().unwrap_or_default();
+        }
+        
+        let mut open = self.show_merge_dialog;
+        let mut merge = false;
+        egui::Window::new("Merge With Another Source")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Rows from the secondary source replace current rows with the same key.");
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.merge_from_sheet, true, "Google Sheet");
+                    ui.radio_value(&mut self.merge_from_sheet, false, "Local CSV");
+                });
+                if self.merge_from_sheet {
+                    ui.label("Google Sheet URL:");
+                    ui.text_edit_singleline(&mut self.merge_url);
+                    ui.label("Sheet Name (optional):");
+                    ui.text_edit_singleline(&mut self.merge_sheet);
+                } else {
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut self.merge_path);
+                        if ui.button("Browse...").clicked() {
+                            if let Some(path) = FileDialog::new().add_filter("Delimited Text", &["csv", "tsv", "txt"]).pick_file() {
+                                self.merge_path = path.display().to_string();
+                            }
+                        }
+                    });
+                }
+                ui.separator();
+                egui::ComboBox::from_label("Key column")
+                    .selected_text(self.merge_key_col.as_str())
+                    .show_ui(ui, |ui| {
+                        for header in &headers {
+                            ui.selectable_value(&mut self.merge_key_col, header.clone(), header);
+                        }
+                    });
+                ui.radio_value(&mut self.merge_strategy, MergeStrategy::ReplaceAndAppend, "Replace matching rows and append new ones");
+                ui.radio_value(&mut self.merge_strategy, MergeStrategy::ReplaceOnly, "Only replace matching rows");
+                ui.separator();
+                let ready = if self.merge_from_sheet { !self.merge_url.is_empty() } else { !self.merge_path.is_empty() };
+                if ui.add_enabled(ready && !self.merge_key_col.is_empty(), egui::Button::new("Merge")).clicked() {
+                    merge = true;
+                }
+            });
+        self.show_merge_dialog = open && !merge;
+        
+        if merge {
+            let secondary = if self.merge_from_sheet {
+                DataSource::Cloud(self.merge_url.clone(), self.merge_sheet.clone())
+            } else {
+                DataSource::Local(PathBuf::from(&self.merge_path))
+            };
+            if let Some(primary) = self.data_source.take() {
+                self.data_source = Some(DataSource::Merged {
+                    primary: Box::new(primary),
+                    secondary: Box::new(secondary),
+                    key_col: self.merge_key_col.clone(),
+                    strategy: self.merge_strategy,
+                });
+                self.reload_data();
+            }
+        }
+    }
+    
+    // Command line that opens the current source in another viewer, if it has one
+    fn duplicate_window_args(&self) -> Option<Vec<String>> {
+        let args = match &self.data_source {
+            // Workbooks open on their first sheet from the command line
+            Some(DataSource::Local(path) | DataSource::Workbook(path, 0)) => vec![
+                "--file".to_string(), path.display().to_string(),
+            ],
+            Some(DataSource::Cloud(url, sheet)) => vec![
+                "--sheet-url".to_string(), url.clone(),
+                "--sheet-name".to_string(), sheet.clone(),
+            ],
+            Some(DataSource::Url(url)) => vec!["--url".to_string(), url.clone()],
+            // Inline and merged sources have no command-line equivalent
+            Some(DataSource::Inline(_) | DataSource::Workbook(..) | DataSource::Merged { .. }) | None => return None,
+        };
+        Some(args)
+    }
+    
+    // Start a second, independent viewer process: eframe 0.22 can only drive one window per process
+    fn open_duplicate_window(&mut self, args: Vec<String>) {
+        let result = std::env::current_exe().and_then(|exe| {
+            let mut command = std::process::Command::new(exe);
+            command.args(args);
+            // The copy stays read-only too; other users can read a command line, not the environment
+            if let Some(pin) = &self.readonly_pin {
+                command.env(PIN_ENV_VAR, pin);
+            }
+            command.spawn()
+        });
+        if let Err(err) = result {
+            app_log::log(format!("Failed to open new window: {}", err));
+            self.show_status(format!("Failed to open new window: {}", err));
+        }
+    }
+    
+    fn update_preview(&mut self) {
+        self.preview_data = self.data.as_ref()
+            .map(|data| data.sample_rows(self.preview_size, self.preview_strategy));
+    }
+    
+    fn data_preview_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_preview_dialog;
+        egui::Window::new("Data Preview")
+            .open(&mut open)
+            .default_size([600.0, 400.0])
+            .show(ctx, |ui| {
+                let headers = self.data.as_ref().map(|data| data.headers.clone()).unwrap_or_default();
+                let mut changed = false;
+                
+                ui.horizontal(|ui| {
+                    ui.label("Rows:");
+                    changed |= ui.add(egui::DragValue::new(&mut self.preview_size).clamp_range(1..=10_000)).changed();
+                });
+                ui.horizontal(|ui| {
+                    changed |= ui.radio_value(&mut self.preview_strategy, SampleStrategy::First, "First rows").changed();
+                    if ui.radio(matches!(self.preview_strategy, SampleStrategy::Random(_)), "Random").clicked() {
+                        self.preview_strategy = SampleStrategy::Random(0);
+                        changed = true;
+                    }
+                    if ui.radio(matches!(self.preview_strategy, SampleStrategy::Stratified(_)), "Stratified").clicked() {
+                        self.preview_strategy = SampleStrategy::Stratified(0);
+                        changed = true;
+                    }
+                });
+                match &mut self.preview_strategy {
+                    SampleStrategy::First => {},
+                    SampleStrategy::Random(seed) => {
+                        ui.horizontal(|ui| {
+                            ui.label("Seed:");
+                            changed |= ui.add(egui::DragValue::new(seed)).changed();
+                        });
+                    },
+                    SampleStrategy::Stratified(col) => {
+                        egui::ComboBox::from_label("Column")
+                            .selected_text(headers.get(*col).cloned().unwrap_or_default())
+                            .show_ui(ui, |ui| {
+                                for (i, header) in headers.iter().enumerate() {
+                                    changed |= ui.selectable_value(col, i, header).changed();
+                                }
+                            });
+                    },
+                }
+                
+                if changed {
+                    self.update_preview();
+                }
+                
+                ui.separator();
+                match &self.preview_data {
+                    Some(preview) => {
+                        ui.label(format!("Showing {} of {} rows", preview.rows.len(),
+                            self.data.as_ref().map_or(0, |data| data.rows.len())));
+                        egui::ScrollArea::both().show(ui, |ui| {
+                            egui::Grid::new("preview_grid")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for header in &preview.headers {
+                                        ui.strong(header);
+                                    }
+                                    ui.end_row();
+                                    
+                                    for row in &preview.rows {
+                                        for cell in row {
+                                            ui.label(cell);
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    },
+                    None => {
+                        ui.label("No data loaded.");
+                    }
+                }
+            });
+        self.show_preview_dialog = open;
+    }
+    
+    fn display_settings_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_display_settings;
+        let column_count = self.data.as_ref().map_or(0, |data| data.headers.len());
+        egui::Window::new("Display Settings")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("display_settings_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Row height:");
+                        ui.add(egui::Slider::new(&mut self.row_height, MIN_ROW_HEIGHT..=MAX_ROW_HEIGHT));
+                        ui.end_row();
+                        
+                        ui.label("Font size:");
+                        ui.add(egui::Slider::new(&mut self.font_size, 6.0..=32.0));
+                        ui.end_row();
+                        
+                        ui.label("Frozen columns:");
+                        ui.add(egui::DragValue::new(&mut self.frozen_columns).clamp_range(0..=column_count));
+                        ui.end_row();
+                        
+                        ui.label("Separator width:");
+                        ui.add(egui::Slider::new(&mut self.freeze_separator_width, 0.5..=8.0).suffix(" px"));
+                        ui.end_row();
+                        
+                        ui.label("Separator color:");
+                        ui.color_edit_button_srgba(&mut self.freeze_separator_color);
+                        ui.end_row();
+                    });
+            });
+        self.show_display_settings = open;
+    }
+    
+    fn base_visuals(&self) -> egui::Visuals {
+        if self.theme_is_dark {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        }
+    }
+    
+    fn annotation_dialog(&mut self, ctx: &egui::Context) {
+        let Some(data) = &self.data else {
+            self.show_annotation_dialog = false;
+            return;
+        };
+        let mut open = self.show_annotation_dialog;
+        let mut add = false;
+        let annotation = &mut self.temp_annotation;
+        egui::Window::new("Add Annotation")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("annotation_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Row:");
+                    let mut row_number = annotation.row + 1;
+                    ui.add(egui::DragValue::new(&mut row_number).clamp_range(1..=data.rows.len().max(1)));
+                    annotation.row = row_number - 1;
+                    ui.end_row();
+                    
+                    ui.label("Column:");
+                    egui::ComboBox::from_id_source("annotation_column")
+                        .selected_text(data.headers.get(annotation.col).map_or("", String::as_str))
+                        .show_ui(ui, |ui| {
+                            for (col, header) in data.headers.iter().enumerate() {
+                                ui.selectable_value(&mut annotation.col, col, header);
+                            }
+                        });
+                    ui.end_row();
+                    
+                    ui.label("Label:");
+                    ui.text_edit_singleline(&mut annotation.text);
+                    ui.end_row();
+                    
+                    ui.label("Color:");
+                    egui::color_picker::color_edit_button_srgba(ui, &mut annotation.color, egui::color_picker::Alpha::Opaque);
+                    ui.end_row();
+                });
+                ui.checkbox(&mut annotation.arrow, "Arrow to the cell");
+                
+                if let Some(row) = data.rows.get(annotation.row) {
+                    ui.weak(format!("Cell value: {}", row.get(annotation.col).map_or("", String::as_str)));
+                }
+                ui.separator();
+                add = ui.add_enabled(!annotation.text.trim().is_empty(), egui::Button::new("Add")).clicked();
+            });
+        if add {
+            self.annotations.push(self.temp_annotation.clone());
+            open = false;
+        }
+        self.show_annotation_dialog = open;
+    }
+    
+    // Labels above-right of their cells, drawn over the table; cells scrolled out of view are skipped
+    fn paint_annotations(&self, ui: &egui::Ui) {
+        let painter = ui.painter();
+        for annotation in &self.annotations {
+            let Some(cell) = self.annotation_cells.get(&(annotation.row, annotation.col)) else {
+                continue;
+            };
+            let anchor = cell.right_top() + ANNOTATION_OFFSET;
+            let background = painter.add(egui::Shape::Noop);
+            let text_rect = painter.text(
+                anchor,
+                egui::Align2::LEFT_BOTTOM,
+                &annotation.text,
+                egui::FontId::proportional(self.font_size * 1.2),
+                egui::Color32::WHITE,
+            );
+            painter.set(background, egui::Shape::rect_filled(text_rect.expand(4.0), 4.0, annotation.color));
+            if annotation.arrow {
+                let start = text_rect.expand(4.0).left_bottom();
+                painter.arrow(start, cell.center() - start, egui::Stroke::new(2.0, annotation.color));
+            } else {
+                painter.rect_stroke(cell.expand(2.0), 2.0, egui::Stroke::new(2.0, annotation.color));
+            }
+        }
+    }
+    
+    // Translate the raw headers no template rule renames yet, on a background thread
+    fn start_header_translation(&mut self) {
+        let Some(data) = &self.data else { return };
+        let header_config = self.header_config();
+        let headers: Vec<String> = data.raw_headers.iter()
+            .filter(|header| !is_column_hidden(header, &header_config))
+            .filter(|header| replace_header(header, &header_config) == **header)
+            .cloned()
+            .collect();
+        
+        let (sender, receiver) = mpsc::channel();
+        let api_url = self.settings.libretranslate_url.clone();
+        std::thread::spawn(move || {
+            let result = translate::translate_headers(&api_url, &headers).map_err(|err| err.to_string());
+            let _ = sender.send(result);
+        });
+        self.translation_receiver = Some(receiver);
+        self.pending_translations.clear();
+        self.translation_error = None;
+        self.show_translation_dialog = true;
+    }
+    
+    fn translation_dialog(&mut self, ctx: &egui::Context) {
+        if let Some(receiver) = &self.translation_receiver {
+            match receiver.try_recv() {
+                Ok(Ok(translations)) => {
+                    self.pending_translations = translations.into_iter()
+                        .map(|(header, translation)| (header, translation, true))
+                        .collect();
+                    self.translation_receiver = None;
+                },
+                Ok(Err(err)) => {
+                    self.translation_error = Some(err);
+                    self.translation_receiver = None;
+                },
+                Err(TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(100)),
+                Err(TryRecvError::Disconnected) => {
+                    self.translation_error = Some("Translation stopped unexpectedly".to_string());
+                    self.translation_receiver = None;
+                },
+            }
+        }
+        
+        let mut open = self.show_translation_dialog;
+        let mut close = false;
+        egui::Window::new("Auto-Translate Headers")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if self.translation_receiver.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Translating headers...");
+                    });
+                    return;
+                }
+                if let Some(error) = &self.translation_error {
+                    ui.colored_label(ui.visuals().error_fg_color, format!("Translation failed: {}", error));
+                } else if self.pending_translations.is_empty() {
+                    ui.label("No headers needed translating.");
+                } else {
+                    ui.label("Review the translations to add as header replacements:");
+                    egui::Grid::new("translation_grid").num_columns(3).striped(true).show(ui, |ui| {
+                        for (header, translation, accepted) in &mut self.pending_translations {
+                            ui.checkbox(accepted, "");
+                            ui.label(header.as_str());
+                            ui.label(format!("→ {}", translation));
+                            ui.end_row();
+                        }
+                    });
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let any_accepted = self.pending_translations.iter().any(|(_, _, accepted)| *accepted);
+                    if ui.add_enabled(any_accepted, egui::Button::new("Apply")).clicked() {
+                        // Ahead of the template's rules so the exact header wins over fragments
+                        for (header, translation, _) in self.pending_translations.drain(..).filter(|(_, _, accepted)| *accepted).rev() {
+                            self.header_config.replacements.insert(0, (header.to_lowercase(), translation));
+                        }
+                        self.reload_requested = true;
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        self.show_translation_dialog = open && !close;
+    }
+    
+    fn stream_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_stream_dialog;
+        let mut close = false;
+        egui::Window::new("NDI Output")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Streams the viewer window as an NDI source through ffmpeg.");
+                egui::Grid::new("stream_output_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Source name:");
+                    ui.text_edit_singleline(&mut self.temp_stream_name);
+                    ui.end_row();
+                    ui.label("Resolution:");
+                    egui::ComboBox::from_id_source("stream_resolution")
+                        .selected_text(self.temp_stream_resolution.label())
+                        .show_ui(ui, |ui| {
+                            for resolution in OutputResolution::ALL {
+                                ui.selectable_value(&mut self.temp_stream_resolution, resolution, resolution.label());
+                            }
+                        });
+                    ui.end_row();
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let name = self.temp_stream_name.trim();
+                    if ui.add_enabled(!name.is_empty(), egui::Button::new("Start")).clicked() {
+                        match StreamOutput::start(name, self.temp_stream_resolution) {
+                            Ok(output) => {
+                                self.show_status(format!("NDI output \"{}\" started at {}", name, output.resolution().label()));
+                                self.stream_output = Some(output);
+                                close = true;
+                            },
+                            Err(err) => self.show_status(format!("Could not start ffmpeg: {}", err)),
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        self.show_stream_dialog = open && !close;
+    }
+    
+    fn update_interval(&self) -> Duration {
+        Duration::from_secs(self.settings.update_interval_secs.max(settings::MIN_UPDATE_INTERVAL_SECS))
+    }
+    
+    fn settings_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_settings_dialog;
+        let mut close = false;
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Update interval:");
+                    ui.add(
+                        egui::Slider::new(
+                            &mut self.temp_settings.update_interval_secs,
+                            settings::MIN_UPDATE_INTERVAL_SECS..=settings::MAX_UPDATE_INTERVAL_SECS,
+                        )
+                        .logarithmic(true)
+                        .suffix(" s"),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("LibreTranslate URL:");
+                    ui.text_edit_singleline(&mut self.temp_settings.libretranslate_url);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Comment file:");
+                    ui.text_edit_singleline(&mut self.temp_settings.comment_file);
+                    if ui.button("Browse...").clicked() {
+                        if let Some(path) = FileDialog::new().add_filter("JSON Files", &["json"]).save_file() {
+                            self.temp_settings.comment_file = path.display().to_string();
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Comment name:");
+                    ui.add(egui::TextEdit::singleline(&mut self.temp_settings.comment_username).hint_text(comment_username_fallback()));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Strike-through codes:");
+                    ui.add(egui::TextEdit::singleline(&mut self.temp_dnx_patterns).hint_text("dns, dnf, dq"));
+                });
+                egui::CollapsingHeader::new("Row Highlight Rules").show(ui, |ui| self.highlight_rules_editor(ui));
+                egui::CollapsingHeader::new("Import Schema").show(ui, |ui| {
+                    ui.checkbox(&mut self.temp_settings.strict_schema, "Strict mode: reject data that doesn't have exactly these columns");
+                    ui.horizontal(|ui| {
+                        ui.label("Expected columns:");
+                        ui.add(egui::TextEdit::singleline(&mut self.temp_expected_columns).hint_text("Name, Surname, Club, Result"));
+                    });
+                    if let Some(data) = &self.data {
+                        if ui.button("Use Current Columns").clicked() {
+                            let loaded = if data.raw_headers.is_empty() { &data.headers } else { &data.raw_headers };
+                            self.temp_expected_columns = loaded.join(", ");
+                        }
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        if self.temp_settings.comment_file != self.settings.comment_file {
+                            self.comments.clear();
+                            self.last_comment_poll = None;
+                        }
+                        self.temp_settings.dnx_patterns = self.temp_dnx_patterns.split(',')
+                            .map(str::trim)
+                            .filter(|pattern| !pattern.is_empty())
+                            .map(String::from)
+                            .collect();
+                        self.temp_settings.expected_columns = self.temp_expected_columns.split(',')
+                            .map(str::trim)
+                            .filter(|column| !column.is_empty())
+                            .map(String::from)
+                            .collect();
+                        // Lists kept up to date behind the dialog's back
+                        let recent_files = std::mem::take(&mut self.settings.recent_files);
+                        let column_configs = std::mem::take(&mut self.settings.column_configs);
+                        let print_header = std::mem::take(&mut self.settings.print_header);
+                        let date_display_format = std::mem::take(&mut self.settings.date_display_format);
+                        self.settings = Settings {
+                            recent_files,
+                            column_configs,
+                            print_header,
+                            date_display_format,
+                            ..self.temp_settings.clone()
+                        };
+                        if let Err(err) = self.settings.save() {
+                            self.show_status(format!("Could not save settings: {}", err));
+                        }
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        self.show_settings_dialog = open && !close;
+    }
+    
+    fn event_info_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_event_info_dialog;
+        let mut close = false;
+        let event_date = NaiveDate::parse_from_str(self.temp_event_date.trim(), "%Y-%m-%d");
+        egui::Window::new("Event Info")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("event_info_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Event name:");
+                    ui.text_edit_singleline(&mut self.temp_print_header.event_name);
+                    ui.end_row();
+                    
+                    ui.label("Date:");
+                    ui.add(egui::TextEdit::singleline(&mut self.temp_event_date).hint_text("YYYY-MM-DD"));
+                    ui.end_row();
+                    
+                    ui.label("Date format:");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut self.temp_date_format).desired_width(100.0));
+                        // A bad format string makes chrono's Display fail, which would panic in to_string
+                        let mut preview = String::new();
+                        match &event_date {
+                            Ok(date) if write!(preview, "{}", date.format(&self.temp_date_format)).is_ok() => ui.weak(preview),
+                            Ok(_) => ui.colored_label(ui.visuals().error_fg_color, "Invalid format"),
+                            Err(_) => ui.colored_label(ui.visuals().error_fg_color, "Invalid date"),
+                        };
+                    });
+                    ui.end_row();
+                    
+                    ui.label("Logo:");
+                    ui.horizontal(|ui| {
+                        let logo = self.temp_print_header.logo_path.as_ref()
+                            .map_or("None".to_string(), |path| path.display().to_string());
+                        ui.label(logo);
+                        if ui.button("Browse...").clicked() {
+                            if let Some(path) = FileDialog::new().add_filter("Images", &["png", "jpg", "jpeg", "svg"]).pick_file() {
+                                self.temp_print_header.logo_path = Some(path);
+                            }
+                        }
+                        if self.temp_print_header.logo_path.is_some() && ui.button("Clear").clicked() {
+                            self.temp_print_header.logo_path = None;
+                        }
+                    });
+                    ui.end_row();
+                    
+                    ui.label("Footer:");
+                    ui.text_edit_singleline(&mut self.temp_print_header.footer_text);
+                    ui.end_row();
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(event_date.is_ok(), egui::Button::new("Save")).clicked() {
+                        let mut print_header = self.temp_print_header.clone();
+                        if let Ok(date) = event_date {
+                            print_header.event_date = date;
+                        }
+                        self.settings.print_header = print_header;
+                        self.settings.date_display_format = self.temp_date_format.clone();
+                        if let Err(err) = self.settings.save() {
+                            self.show_status(format!("Could not save settings: {}", err));
+                        }
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        self.show_event_info_dialog = open && !close;
+    }
+    
+    // Rules apply as they are edited rather than on Save
+    fn highlight_rules_editor(&mut self, ui: &mut egui::Ui) {
+        let Some(data) = &self.data else {
+            ui.label("No data loaded");
+            return;
+        };
+        let mut remove = None;
+        egui::Grid::new("highlight_rules").num_columns(5).show(ui, |ui| {
+            for (index, rule) in self.highlight_rules.iter_mut().enumerate() {
+                egui::ComboBox::from_id_source(("highlight_column", index))
+                    .selected_text(data.headers.get(rule.column_index).map_or("", String::as_str))
+                    .show_ui(ui, |ui| {
+                        for (col, header) in data.headers.iter().enumerate() {
+                            ui.selectable_value(&mut rule.column_index, col, header);
+                        }
+                    });
+                egui::ComboBox::from_id_source(("highlight_comparator", index))
+                    .selected_text(rule.comparator.symbol())
+                    .width(50.0)
+                    .show_ui(ui, |ui| {
+                        for op in CompareOp::ALL {
+                            ui.selectable_value(&mut rule.comparator, op, op.symbol());
+                        }
+                    });
+                ui.add(egui::TextEdit::singleline(&mut rule.threshold).desired_width(80.0));
+                ui.color_edit_button_srgba(&mut rule.color);
+                if ui.small_button("✕").on_hover_text("Remove rule").clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(index) = remove {
+            self.highlight_rules.remove(index);
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Add Rule").clicked() {
+                self.highlight_rules.push(HighlightRule {
+                    column_index: 0,
+                    comparator: CompareOp::Eq,
+                    threshold: String::new(),
+                    color: GOLD_HIGHLIGHT,
+                });
+            }
+            if ui.button("Top 3 Ranks").on_hover_text("Gold, silver and bronze for ranks 1 to 3").clicked() {
+                self.add_top_three_rules();
+            }
+            if ui.button("Personal Bests").on_hover_text("Rows marked PB").clicked() {
+                self.add_personal_best_rule();
+            }
+        });
+    }
+    
+    fn add_top_three_rules(&mut self) {
+        let Some(data) = &self.data else { return };
+        let Some(col) = data.headers.iter()
+            .position(|header| RANK_COLUMNS.iter().any(|name| header.eq_ignore_ascii_case(name))) else {
+            self.show_status("No rank column to highlight".to_string());
+            return;
+        };
+        for (rank, color) in [("1", GOLD_HIGHLIGHT), ("2", SILVER_HIGHLIGHT), ("3", BRONZE_HIGHLIGHT)] {
+            self.highlight_rules.push(HighlightRule {
+                column_index: col,
+                comparator: CompareOp::Eq,
+                threshold: rank.to_string(),
+                color,
+            });
+        }
+    }
+    
+    // On the column where the PB markers are
+    fn add_personal_best_rule(&mut self) {
+        let Some(data) = &self.data else { return };
+        let Some(col) = (0..data.headers.len())
+            .find(|&col| data.rows.iter().any(|row| row.get(col).is_some_and(|cell| cell.trim().eq_ignore_ascii_case(PB_MARKER)))) else {
+            self.show_status("No PB markers in the data".to_string());
+            return;
+        };
+        self.highlight_rules.push(HighlightRule {
+            column_index: col,
+            comparator: CompareOp::Eq,
+            threshold: PB_MARKER.to_string(),
+            color: PB_HIGHLIGHT,
+        });
+    }
+    
+    fn poll_comments(&mut self) {
+        self.last_comment_poll = Some(Instant::now());
+        if self.settings.comment_file.is_empty() {
+            return;
+        }
+        match comments::read_comments(&self.settings.comment_file) {
+            Ok(comments) => {
+                self.comments = comments;
+                self.comment_error = None;
+            },
+            Err(err) => self.comment_error = Some(format!("Could not read comments: {}", err)),
+        }
+    }
+    
+    fn post_comment(&mut self) {
+        let message = self.comment_input.trim().to_string();
+        if message.is_empty() || self.settings.comment_file.is_empty() {
+            return;
+        }
+        let user = if self.settings.comment_username.trim().is_empty() {
+            comment_username_fallback()
+        } else {
+            self.settings.comment_username.trim().to_string()
+        };
+        match comments::append_comment(&self.settings.comment_file, &user, &message) {
+            Ok(comments) => {
+                self.comments = comments;
+                self.comment_input.clear();
+                self.comment_error = None;
+            },
+            Err(err) => self.comment_error = Some(format!("Could not post comment: {}", err)),
+        }
+    }
+    
+    fn comment_panel(&mut self, ctx: &egui::Context) {
+        egui::SidePanel::right("comment_feed")
+            .resizable(true)
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Comments");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").on_hover_text("Hide comments").clicked() {
+                            self.show_comment_panel = false;
+                        }
+                    });
+                });
+                ui.separator();
+                
+                if self.settings.comment_file.is_empty() {
+                    ui.label("Choose a shared comment file in View > Settings... to start the feed.");
+                    return;
+                }
+                if let Some(error) = &self.comment_error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                
+                // Input pinned to the bottom, history filling the rest
+                egui::TopBottomPanel::bottom("comment_input").show_inside(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let input = ui.add(egui::TextEdit::singleline(&mut self.comment_input).hint_text("Write a comment"));
+                        let submitted = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        if ui.button("Send").clicked() || submitted {
+                            self.post_comment();
+                            input.request_focus();
+                        }
+                    });
+                });
+                egui::ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for (user, message) in &self.comments {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.strong(format!("{}:", user));
+                                ui.label(message);
+                            });
+                        }
+                    });
+            });
+    }
+    
+    fn log_panel(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_log_panel;
+        egui::Window::new("Log")
+            .open(&mut open)
+            .default_size([420.0, 240.0])
+            .show(ctx, |ui| {
+                if ui.button("Clear").clicked() {
+                    app_log::clear();
+                }
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for entry in app_log::entries() {
+                            ui.monospace(entry);
+                        }
+                    });
+            });
+        self.show_log_panel = open;
+    }
+    
+    fn theme_editor_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_theme_editor;
+        let mut visuals = self.custom_visuals.clone().unwrap_or_else(|| self.base_visuals());
+        let mut message = None;
+        
+        egui::Window::new("Theme Editor")
+            .open(&mut open)
+            .default_size([360.0, 480.0])
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for (category, fields) in theme::THEME_COLORS {
+                        egui::CollapsingHeader::new(*category)
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                egui::Grid::new(category).num_columns(2).show(ui, |ui| {
+                                    for (_, label, field) in fields.iter() {
+                                        ui.label(*label);
+                                        ui.color_edit_button_srgba(field(&mut visuals));
+                                        ui.end_row();
+                                    }
+                                });
+                            });
+                    }
+                });
+                
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Save Theme...").clicked() {
+                        let mut dialog = FileDialog::new()
+                            .add_filter("Theme Files", &["toml"])
+                            .set_file_name(&format!("custom.{}", theme::THEME_EXTENSION));
+                        // Without the themes folder the dialog just opens in its default location
+                        match theme::theme_dir().map(|dir| std::fs::create_dir_all(&dir).map(|_| dir)) {
+                            Some(Ok(dir)) => dialog = dialog.set_directory(dir),
+                            Some(Err(err)) => app_log::log(format!("Could not create the themes folder: {}", err)),
+                            None => {}
+                        }
+                        if let Some(path) = dialog.save_file() {
+                            message = Some(match theme::save_theme(&path, &visuals) {
+                                Ok(()) => format!("Saved theme to {}", path.display()),
+                                Err(err) => format!("Failed to save theme: {}", err),
+                            });
+                        }
+                    }
+                    if ui.button("Load Theme...").clicked() {
+                        let mut dialog = FileDialog::new().add_filter("Theme Files", &["toml"]);
+                        if let Some(dir) = theme::theme_dir().filter(|dir| dir.is_dir()) {
+                            dialog = dialog.set_directory(dir);
+                        }
+                        if let Some(path) = dialog.pick_file() {
+                            match theme::load_theme(&path) {
+                                Ok(loaded) => visuals = loaded,
+                                Err(err) => message = Some(format!("Failed to load theme: {}", err)),
+                            }
+                        }
+                    }
+                    if ui.button("Reset").clicked() {
+                        visuals = self.base_visuals();
+                    }
+                });
+            });
+        
+        self.custom_visuals = Some(visuals);
+        self.show_theme_editor = open;
+        if let Some(message) = message {
+            app_log::log(message.clone());
+            self.show_status(message);
+        }
+    }
+    
+    fn restore_default_sizes(&mut self) {
+        self.fit_to_screen = false;
+        if self.accessibility_mode {
+            self.row_height = ACCESSIBLE_ROW_HEIGHT;
+            self.font_size = ACCESSIBLE_FONT_SIZE;
+        } else {
+            self.row_height = DEFAULT_ROW_HEIGHT;
+            self.font_size = DEFAULT_FONT_SIZE;
+        }
+    }
+    
+    fn apply_style(&self, ctx: &egui::Context) {
+        let mut style = egui::Style::default();
+        if self.accessibility_mode {
+            // Larger text everywhere, not just in the table
+            for (text_style, font_id) in style.text_styles.iter_mut() {
+                font_id.size = match text_style {
+                    egui::TextStyle::Heading => ACCESSIBLE_FONT_SIZE * 1.3,
+                    egui::TextStyle::Small => ACCESSIBLE_FONT_SIZE * 0.8,
+                    _ => ACCESSIBLE_FONT_SIZE,
+                };
+            }
+            style.visuals = theme::high_contrast_visuals();
+        } else if let Some(visuals) = &self.custom_visuals {
+            style.visuals = visuals.clone();
+        } else {
+            style.visuals = self.base_visuals();
+        }
+        ctx.set_style(style);
+    }
+    
+    // Row height and font size that make every row (plus the header) fit in the available height
+    fn fit_to_screen_sizes(ui: &egui::Ui, data: &TableData) -> (f32, f32) {
+        let row_count = (data.rows.len() + 1) as f32;
+        let spacing = ui.spacing().item_spacing.y;
+        let row_height = (ui.available_height() / row_count - spacing)
+            .clamp(MIN_ROW_HEIGHT, MAX_ROW_HEIGHT);
+        let font_size = DEFAULT_FONT_SIZE * row_height / DEFAULT_ROW_HEIGHT;
+        (row_height, font_size)
+    }
+    
+    fn column_statistics_panel(ui: &mut egui::Ui, data: &TableData) {
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("stats_grid")
+                .striped(true)
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for (col, header) in data.headers.iter().enumerate() {
+                        let (Some(values), Some(stats)) = (data.numeric_values(col), data.column_statistics(col)) else {
+                            continue;
+                        };
+                        
+                        ui.strong(header);
+                        ui.label(format!("n={}  min={}  max={}  mean={:.2}", stats.count, stats.min, stats.max, stats.mean));
+                        Self::histogram(ui, &values, stats.min, stats.max);
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+    
+    fn distribution_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_distribution_window;
+        egui::Window::new("Column Distribution")
+            .open(&mut open)
+            .default_size([520.0, 360.0])
+            .show(ctx, |ui| {
+                let Some(data) = &self.data else {
+                    ui.label("No data loaded.");
+                    return;
+                };
+                let numeric_columns: Vec<usize> = (0..data.headers.len())
+                    .filter(|&col| data.numeric_values(col).is_some())
+                    .collect();
+                let Some(&first_numeric) = numeric_columns.first() else {
+                    ui.label("No numeric columns.");
+                    return;
+                };
+                let mut col = self.distribution_column.as_ref()
+                    .and_then(|name| data.headers.iter().position(|header| header == name))
+                    .filter(|col| numeric_columns.contains(col))
+                    .unwrap_or(first_numeric);
+                
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("distribution_column")
+                        .selected_text(data.headers[col].as_str())
+                        .show_ui(ui, |ui| {
+                            for &numeric in &numeric_columns {
+                                ui.selectable_value(&mut col, numeric, &data.headers[numeric]);
+                            }
+                        });
+                    ui.label("Bins:");
+                    ui.add(egui::DragValue::new(&mut self.distribution_bins).clamp_range(1..=100));
+                });
+                self.distribution_column = Some(data.headers[col].clone());
+                
+                let (Some(values), Some(stats)) = (data.numeric_values(col), data.column_statistics(col)) else {
+                    return;
+                };
+                ui.strong(format!(
+                    "{} — n = {}, mean {:.2}, SD {:.2}, min {}, max {}",
+                    data.headers[col], stats.count, stats.mean, stats.stddev, stats.min, stats.max
+                ));
+                distribution_plot(ui, &values, &stats, self.distribution_bins);
+            });
+        self.show_distribution_window = open;
+    }
+    
+    fn aggregate_sidebar(&mut self, ui: &mut egui::Ui, data: &TableData) {
+        let numeric_columns: Vec<usize> = (0..data.headers.len())
+            .filter(|&col| data.numeric_values(col).is_some())
+            .collect();
+        let Some(&first_numeric) = numeric_columns.first() else {
+            ui.label("No numeric columns.");
+            return;
+        };
+        let col = self.aggregate_column.as_ref()
+            .and_then(|name| data.headers.iter().position(|header| header == name))
+            .filter(|col| numeric_columns.contains(col))
+            .unwrap_or(first_numeric);
+        
+        let mut selected = col;
+        egui::ComboBox::from_id_source("aggregate_column")
+            .selected_text(data.headers[col].as_str())
+            .show_ui(ui, |ui| {
+                for &numeric in &numeric_columns {
+                    ui.selectable_value(&mut selected, numeric, &data.headers[numeric]);
+                }
+            });
+        self.aggregate_column = Some(data.headers[selected].clone());
+        
+        // Only rows that pass the current filters take part
+        let mut entries: Vec<(f64, String)> = data.rows.iter()
+            .filter(|row| self.row_passes_filters(data, row))
+            .filter_map(|row| {
+                let value = row.get(selected)?.trim().parse::<f64>().ok()?;
+                Some((value, data.row_key(row)))
+            })
+            .collect();
+        entries.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        
+        let top: Vec<&(f64, String)> = entries.iter().take(LEADERBOARD_SIZE).collect();
+        let bottom: Vec<&(f64, String)> = entries.iter().rev().take(LEADERBOARD_SIZE).collect();
+        Self::leaderboard_card(ui, &format!("Top {}", LEADERBOARD_SIZE), &top);
+        Self::leaderboard_card(ui, &format!("Bottom {}", LEADERBOARD_SIZE), &bottom);
+    }
+    
+    fn leaderboard_card(ui: &mut egui::Ui, title: &str, entries: &[&(f64, String)]) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_min_width(ui.available_width());
+            ui.strong(title);
+            for (place, (value, name)) in entries.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}.", place + 1));
+                    ui.label(name);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.strong(value.to_string());
+                    });
+                });
+            }
+        });
+    }
+    
+    // Mini histogram of `values` split into equal-width bins between min and max
+    fn histogram(ui: &mut egui::Ui, values: &[f64], min: f64, max: f64) {
+        let bin_width = (max - min) / HISTOGRAM_BINS as f64;
+        let mut counts = [0usize; HISTOGRAM_BINS];
+        for value in values {
+            let bin = if bin_width > 0.0 { ((value - min) / bin_width) as usize } else { 0 };
+            counts[bin.min(HISTOGRAM_BINS - 1)] += 1;
+        }
+        let max_count = counts.iter().copied().max().unwrap_or(1).max(1);
+        
+        let (rect, response) = ui.allocate_exact_size(HISTOGRAM_SIZE, egui::Sense::hover());
+        let bar_width = rect.width() / HISTOGRAM_BINS as f32;
+        let color = ui.visuals().selection.bg_fill;
+        for (bin, &count) in counts.iter().enumerate() {
+            let height = rect.height() * count as f32 / max_count as f32;
+            let left = rect.left() + bin as f32 * bar_width;
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(left + 0.5, rect.bottom() - height),
+                egui::pos2(left + bar_width - 0.5, rect.bottom()),
+            );
+            ui.painter().rect_filled(bar, 0.0, color);
+        }
+        
+        if let Some(pos) = response.hover_pos() {
+            let bin = (((pos.x - rect.left()) / bar_width) as usize).min(HISTOGRAM_BINS - 1);
+            let start = min + bin as f64 * bin_width;
+            response.on_hover_text(format!("{:.2} – {:.2}: {}", start, start + bin_width, counts[bin]));
+        }
+    }
+    
+    fn display_table(&mut self, ui: &mut egui::Ui, data: &TableData) {
+        // Fit to screen temporarily overrides the configured sizes
+        let (row_height, font_size) = if self.fit_to_screen {
+            Self::fit_to_screen_sizes(ui, data)
+        } else {
+            (self.row_height, self.font_size)
+        };
+        
+        let mut columns = self.responsive_columns(ui, data, font_size);
+        self.collapse_column_groups(&mut columns);
+        let rows = self.display_rows(data);
+        self.search_matches = rows.iter()
+            .flat_map(|&row_idx| columns.iter().map(move |&col| (row_idx, col)))
+            .filter(|&(row_idx, col)| self.is_search_match(data.rows[row_idx].get(col).map_or("", String::as_str)))
+            .collect();
+        if self.search_focus.is_some_and(|focus| focus >= self.search_matches.len()) {
+            self.search_focus = None;
+        }
+        let view = TableView {
+            data,
+            rows,
+            row_height,
+            font_size,
+        };
+        // The strip is laid out from the column positions recorded while drawing the previous frame
+        if self.show_total_row {
+            self.total_row_strip(ui, &view, &columns);
+        }
+        self.column_left.clear();
+        self.main_row_centers.clear();
+        self.annotation_cells.clear();
+        
+        let show_gutter = self.edit_mode || !self.locked_rows.is_empty();
+        let frozen = self.frozen_columns.min(columns.len());
+        let scroll_to_top = std::mem::take(&mut self.scroll_to_top);
+        if frozen == 0 {
+            let mut scroll_area = egui::ScrollArea::both();
+            if scroll_to_top {
+                scroll_area = scroll_area.vertical_scroll_offset(0.0);
+            }
+            let output = scroll_area.show(ui, |ui| {
+                self.table_grid(ui, "data_grid", &view, &columns, show_gutter);
+            });
+            self.scroll_to_top_button(ui, output.inner_rect, output.state.offset.y);
+            return;
+        }
+        
+        // Frozen columns get their own scroll area that follows the vertical offset of the main one
+        let offset_id = ui.make_persistent_id("table_scroll_offset");
+        let offset: f32 = if scroll_to_top {
+            0.0
+        } else {
+            ui.data(|d| d.get_temp(offset_id)).unwrap_or(0.0)
+        };
+        
+        ui.horizontal_top(|ui| {
+            let frozen_output = egui::ScrollArea::vertical()
+                .id_source("frozen_columns")
+                .vertical_scroll_offset(offset)
+                .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::AlwaysHidden)
+                .show(ui, |ui| {
+                    self.table_grid(ui, "frozen_grid", &view, &columns[..frozen], show_gutter);
+                });
+            
+            // Separator between frozen and scrolling columns
+            let x = frozen_output.inner_rect.right()
+                + ui.spacing().item_spacing.x
+                + self.freeze_separator_width / 2.0;
+            ui.painter().vline(
+                x,
+                frozen_output.inner_rect.y_range(),
+                egui::Stroke::new(self.freeze_separator_width, self.freeze_separator_color),
+            );
+            ui.add_space(self.freeze_separator_width);
+            
+            let main_output = egui::ScrollArea::both()
+                .id_source("scrolling_columns")
+                .vertical_scroll_offset(offset)
+                .show(ui, |ui| {
+                    self.table_grid(ui, "data_grid", &view, &columns[frozen..], false);
+                });
+            
+            let new_offset = if frozen_output.state.offset.y != offset {
+                frozen_output.state.offset.y
+            } else {
+                main_output.state.offset.y
+            };
+            ui.data_mut(|d| d.insert_temp(offset_id, new_offset));
+            self.scroll_to_top_button(ui, main_output.inner_rect, new_offset);
+        });
+    }
+    
+    // Floating "Top" button, shown once the table is scrolled more than a screen down
+    fn scroll_to_top_button(&mut self, ui: &egui::Ui, table_rect: egui::Rect, offset: f32) {
+        if offset <= table_rect.height() {
+            return;
+        }
+        egui::Area::new("scroll_to_top")
+            .order(egui::Order::Foreground)
+            .fixed_pos(table_rect.right_top() + egui::vec2(-80.0, 8.0))
+            .show(ui.ctx(), |ui| {
+                if ui.button("⬆ Top").clicked() {
+                    self.scroll_to_top = true;
+                }
+            });
+    }
+    
+    fn is_column_visible(&self, header: &str) -> bool {
+        self.column_visibility.get(header).copied()
+            .unwrap_or_else(|| !self.auto_hidden_columns.contains(header))
+    }
+    
+    fn columns_menu(&mut self, ui: &mut egui::Ui) {
+        let Some(data) = &self.data else {
+            ui.label("No data loaded");
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label("Freeze first");
+            ui.add(egui::DragValue::new(&mut self.frozen_columns).clamp_range(0..=data.headers.len()));
+            ui.label("columns");
+        });
+        ui.separator();
+        // Every column in the source: loaded ones toggle instantly, ones the template hides
+        // need a re-parse to bring them in
+        let header_config = self.header_config();
+        let mut loaded_col = 0;
+        let mut reveal = None;
+        let mut changed = false;
+        for raw_header in &data.raw_headers {
+            if is_column_hidden(raw_header, &header_config) {
+                let mut visible = false;
+                if ui.checkbox(&mut visible, format!("{} (hidden by template)", raw_header)).changed() {
+                    reveal = Some((raw_header.clone(), true));
+                }
+                continue;
+            }
+            // Where the user has moved this loaded column to
+            let position = match self.column_order.iter().position(|&col| col == loaded_col) {
+                Some(position) => position,
+                None if self.column_order.is_empty() => loaded_col,
+                None => break,
+            };
+            loaded_col += 1;
+            let Some(header) = data.headers.get(position) else { break };
+            let mut visible = self.is_column_visible(header);
+            let label = if self.auto_hidden_columns.contains(header) {
+                format!("{} (auto-hidden — all empty)", header)
+            } else {
+                header.clone()
+            };
+            if ui.checkbox(&mut visible, label).changed() {
+                if !visible && self.column_overrides.get(raw_header) == Some(&true) {
+                    // Revealed by an override: drop it so the template hides the column again
+                    reveal = Some((raw_header.clone(), false));
+                } else {
+                    self.column_visibility.insert(header.clone(), visible);
+                    self.active_preset = None;
+                    changed = true;
+                }
+            }
+        }
+        ui.separator();
+        let reordered = self.column_order.iter().enumerate().any(|(position, &col)| position != col);
+        if ui.add_enabled(reordered, egui::Button::new("Reset Column Order")).clicked() {
+            self.reset_column_order();
+            ui.close_menu();
+        }
+        let saved = self.data_source.as_ref()
+            .is_some_and(|source| self.settings.column_configs.contains_key(&source.cache_key()));
+        if ui.add_enabled(saved, egui::Button::new("Forget Column Config for This Source")).clicked() {
+            self.forget_column_config();
+            ui.close_menu();
+        }
+        if changed {
+            self.save_column_config();
+        }
+        if let Some((raw_header, shown)) = reveal {
+            if shown {
+                self.column_overrides.insert(raw_header, true);
+            } else {
+                self.column_overrides.remove(&raw_header);
+            }
+            self.reload_requested = true;
+        }
+    }
+    
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        // The search bar takes Escape for itself while it is open
+        let search_open = self.show_search_bar;
+        self.handle_search_shortcuts(ctx);
+        
+        let (palette, open_file, refresh, focus_filter, escape) = ctx.input(|i| (
+            i.modifiers.command && i.key_pressed(egui::Key::K),
+            i.modifiers.command && i.key_pressed(egui::Key::O),
+            i.modifiers.command && i.key_pressed(egui::Key::R),
+            i.modifiers.command && i.modifiers.shift && i.key_pressed(egui::Key::F),
+            i.key_pressed(egui::Key::Escape),
+        ));
+        if palette {
+            self.show_command_palette = !self.show_command_palette;
+            self.command_palette_query.clear();
+            self.command_palette_selected = 0;
+        }
+        if open_file && !self.source_locked {
+            self.request_pin_action(PinAction::OpenFile);
+        }
+        if refresh {
+            self.refresh_data();
+        }
+        if focus_filter && self.data.is_some() {
+            ctx.memory_mut(|m| m.request_focus(egui::Id::new("filter_input")));
+        }
+        if escape && !search_open && !self.close_dialogs() {
+            self.filter_text.clear();
+        }
+        
+        let open_sort = ctx.input(|i| i.modifiers.command && i.modifiers.shift && i.key_pressed(egui::Key::S));
+        if open_sort && self.data.is_some() {
+            self.open_sort_dialog();
+        }
+        
+        // Ctrl+1..9 switch column presets
+        const DIGIT_KEYS: [egui::Key; 9] = [
+            egui::Key::Num1, egui::Key::Num2, egui::Key::Num3,
+            egui::Key::Num4, egui::Key::Num5, egui::Key::Num6,
+            egui::Key::Num7, egui::Key::Num8, egui::Key::Num9,
+        ];
+        let pressed = ctx.input(|i| {
+            if !i.modifiers.command {
+                return None;
+            }
+            DIGIT_KEYS.iter().position(|&key| i.key_pressed(key))
+        });
+        let Some(digit) = pressed.and_then(|index| char::from_digit(index as u32 + 1, 10)) else {
+            return;
+        };
+        if let Some(index) = self.column_presets.iter().position(|preset| preset.key == digit) {
+            self.apply_column_preset(index);
+        }
+    }
+    
+    // Escape closes every open dialog at once; false if none was open
+    fn close_dialogs(&mut self) -> bool {
+        let dialogs = [
+            &mut self.show_cloud_dialog,
+            &mut self.show_url_dialog,
+            &mut self.show_preview_dialog,
+            &mut self.show_display_settings,
+            &mut self.show_csv_options_dialog,
+            &mut self.show_theme_editor,
+            &mut self.show_merge_dialog,
+            &mut self.show_caption_dialog,
+            &mut self.show_presets_dialog,
+            &mut self.show_sort_dialog,
+            &mut self.show_unlock_confirm,
+            &mut self.show_distribution_window,
+            &mut self.show_precision_dialog,
+            &mut self.show_reorder_dialog,
+            &mut self.show_auto_hide_dialog,
+            &mut self.show_qr_window,
+            &mut self.show_export_schedule_dialog,
+            &mut self.show_athlete_profile,
+            &mut self.show_settings_dialog,
+            &mut self.show_annotation_dialog,
+            &mut self.show_translation_dialog,
+            &mut self.show_stream_dialog,
+            &mut self.show_sheet_export_dialog,
+            &mut self.show_event_info_dialog,
+            &mut self.show_command_palette,
+        ];
+        let mut closed = self.pending_workbook.take().is_some();
+        closed |= self.compared_rows.take().is_some();
+        closed |= !std::mem::take(&mut self.schema_errors).is_empty();
+        for open in dialogs {
+            closed |= std::mem::take(open);
+        }
+        closed
+    }
+    
+    fn reorder_columns_dialog(&mut self, ctx: &egui::Context) {
+        let headers = self.data.as_ref().map(|data| data.headers.clone()).unwrap_or_default();
+        if self.temp_column_order.len() != headers.len() {
+            self.show_reorder_dialog = false;
+            return;
+        }
+        
+        let mut open = self.show_reorder_dialog;
+        let mut apply = false;
+        egui::Window::new("Reorder Columns")
+            .open(&mut open)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                let count = self.temp_column_order.len();
+                let mut moved = None;
+                egui::ScrollArea::vertical().max_height(350.0).show(ui, |ui| {
+                    egui::Grid::new("reorder_columns_grid")
+                        .striped(true)
+                        .num_columns(4)
+                        .show(ui, |ui| {
+                            for position in 0..count {
+                                ui.label(format!("{}.", position + 1));
+                                ui.label(&headers[self.temp_column_order[position]]);
+                                ui.horizontal(|ui| {
+                                    if ui.add_enabled(position > 0, egui::Button::new("▲").small()).clicked() {
+                                        moved = Some((position, position - 1));
+                                    }
+                                    if ui.add_enabled(position + 1 < count, egui::Button::new("▼").small()).clicked() {
+                                        moved = Some((position, position + 1));
+                                    }
+                                });
+                                let target = ui.add(
+                                    egui::DragValue::new(&mut self.temp_column_targets[position])
+                                        .clamp_range(1..=count)
+                                        .prefix("→ "),
+                                ).on_hover_text("Type a position and press Enter");
+                                if (target.lost_focus() || target.drag_released()) && self.temp_column_targets[position] != position + 1 {
+                                    moved = Some((position, self.temp_column_targets[position] - 1));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+                if let Some((from, to)) = moved {
+                    let column = self.temp_column_order.remove(from);
+                    self.temp_column_order.insert(to, column);
+                    self.temp_column_targets = (1..=count).collect();
+                }
+                
+                ui.separator();
+                if ui.button("Apply").clicked() {
+                    apply = true;
+                }
+            });
+        self.show_reorder_dialog = open && !apply;
+        
+        if apply {
+            let order = std::mem::take(&mut self.temp_column_order);
+            self.apply_column_order(&order);
+            self.save_column_config();
+        }
+    }
+    
+    // `order` lists current column indices in their new order; state indexed by column follows along
+    fn apply_column_order(&mut self, order: &[usize]) {
+        let Some(data) = &mut self.data else {
+            return;
+        };
+        let mut current: Vec<usize> = (0..data.headers.len()).collect();
+        for (target, &column) in order.iter().enumerate() {
+            let Some(from) = current.iter().position(|&c| c == column) else {
+                continue;
+            };
+            if from != target {
+                data.reorder_columns(from, target);
+                let moved = current.remove(from);
+                current.insert(target, moved);
+            }
+        }
+        
+        let column_order: Vec<usize> = current.iter()
+            .map(|&old| self.column_order.get(old).copied().unwrap_or(old))
+            .collect();
+        self.column_order = column_order;
+        let new_index = |old: usize| current.iter().position(|&c| c == old);
+        self.column_priority = current.iter()
+            .map(|&old| self.column_priority.get(old).copied().unwrap_or(1))
+            .collect();
+        self.total_row_ops = current.iter()
+            .map(|&old| self.total_row_ops.get(old).copied().unwrap_or(TotalOp::None))
+            .collect();
+        self.column_types = current.iter()
+            .map(|&old| self.column_types.get(old).copied().unwrap_or(false))
+            .collect();
+        self.sort_keys = self.sort_keys.iter()
+            .filter_map(|&(col, descending)| Some((new_index(col)?, descending)))
+            .collect();
+        self.highlight_rules.retain_mut(|rule| match new_index(rule.column_index) {
+            Some(col) => {
+                rule.column_index = col;
+                true
+            },
+            None => false,
+        });
+        // Groups are index ranges over adjacent columns, so they are found again in the new order
+        self.column_groups = match (&self.data, self.auto_group_prefixes) {
+            (Some(data), true) => data.prefix_groups(),
+            _ => Vec::new(),
+        };
+        self.cell_change_times.clear();
+        self.update_expression_filter();
+    }
+    
+    // Pre-fill the dialog with the current sort so it can be tweaked
+    fn open_sort_dialog(&mut self) {
+        if let Some(data) = &self.data {
+            self.temp_sort_spec = self.sort_keys.iter()
+                .filter_map(|&(col, descending)| {
+                    let header = data.headers.get(col)?;
+                    Some(format!("{} {}", header, if descending { "DESC" } else { "ASC" }))
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+        self.sort_errors.clear();
+        self.show_sort_dialog = true;
+    }
+    
+    fn sort_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_sort_dialog;
+        let mut close = false;
+        egui::Window::new("Sort by Column Name")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("One column per line, optionally followed by ASC or DESC:");
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.temp_sort_spec)
+                        .hint_text("Result DESC\nSurname")
+                        .desired_rows(4),
+                );
+                for error in &self.sort_errors {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Sort").clicked() {
+                        let headers = self.data.as_ref().map(|data| data.headers.as_slice()).unwrap_or_default();
+                        match parse_sort_spec(&self.temp_sort_spec, headers) {
+                            Ok(keys) => {
+                                self.sort_keys = keys;
+                                self.scroll_to_selection();
+                                close = true;
+                            },
+                            Err(errors) => self.sort_errors = errors,
+                        }
+                    }
+                    if ui.button("Clear Sort").clicked() {
+                        self.sort_keys.clear();
+                        close = true;
+                    }
+                });
+            });
+        self.show_sort_dialog = open && !close;
+    }
+    
+    // Header clicks cycle ascending -> descending -> load order, replacing any multi-column sort
+    // Shift+Click adds `col` as the next sort key, or flips it if it already is one
+    fn cycle_header_sort(&mut self, col: usize, shift: bool) {
+        if shift {
+            match self.sort_keys.iter_mut().find(|(key, _)| *key == col) {
+                Some((_, descending)) => *descending = !*descending,
+                None => self.sort_keys.push((col, false)),
+            }
+            self.scroll_to_selection();
+            return;
+        }
+        self.sort_keys = match self.sort_keys.as_slice() {
+            [(key, false)] if *key == col => vec![(col, true)],
+            [(key, true)] if *key == col => Vec::new(),
+            // Back to sorting by the primary key alone, in the other direction
+            [(key, descending), ..] if *key == col => vec![(col, !*descending)],
+            _ => vec![(col, false)],
+        };
+        self.scroll_to_selection();
+    }
+    
+    // Selections hold data row indices, so they survive a re-sort; only the view has to follow
+    fn scroll_to_selection(&mut self) {
+        self.scroll_to_row = self.selection_anchor
+            .filter(|anchor| self.selected_rows.contains(anchor))
+            .or_else(|| self.selected_rows.iter().min().copied());
+    }
+    
+    fn compare_rows(&self, data: &TableData, a: usize, b: usize) -> std::cmp::Ordering {
+        for &(col, descending) in &self.sort_keys {
+            let left = data.rows[a].get(col).map_or("", String::as_str);
+            let right = data.rows[b].get(col).map_or("", String::as_str);
+            let ordering = compare_values(left, right);
+            if ordering.is_ne() {
+                return if descending { ordering.reverse() } else { ordering };
+            }
+        }
+        std::cmp::Ordering::Equal
+    }
+    
+    fn apply_column_preset(&mut self, index: usize) {
+        let Some(data) = &self.data else {
+            return;
+        };
+        let preset = &self.column_presets[index];
+        for (col, header) in data.headers.iter().enumerate() {
+            let visible = preset.visible_columns.get(col).copied().unwrap_or(true);
+            self.column_visibility.insert(header.clone(), visible);
+        }
+        self.active_preset = Some(index);
+        self.save_column_config();
+    }
+    
+    fn column_presets_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_presets_dialog;
+        egui::Window::new("Column Presets")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut apply = None;
+                let mut remove = None;
+                if self.column_presets.is_empty() {
+                    ui.label("No presets yet. Choose columns in View > Columns, then save them here.");
+                } else {
+                    egui::Grid::new("column_presets_grid")
+                        .striped(true)
+                        .num_columns(4)
+                        .show(ui, |ui| {
+                            for (index, preset) in self.column_presets.iter().enumerate() {
+                                ui.label(format!("Ctrl+{}", preset.key));
+                                ui.label(&preset.name);
+                                if ui.button("Apply").clicked() {
+                                    apply = Some(index);
+                                }
+                                if ui.button("🗑").on_hover_text("Delete preset").clicked() {
+                                    remove = Some(index);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                }
+                if let Some(index) = apply {
+                    self.apply_column_preset(index);
+                }
+                if let Some(index) = remove {
+                    self.column_presets.remove(index);
+                    self.active_preset = None;
+                }
+                
+                ui.separator();
+                let free_key = ('1'..='9').find(|key| self.column_presets.iter().all(|preset| preset.key != *key));
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.temp_preset_name);
+                    let can_save = free_key.is_some() && self.data.is_some() && !self.temp_preset_name.trim().is_empty();
+                    if ui.add_enabled(can_save, egui::Button::new("Save Current Columns")).clicked() {
+                        if let (Some(key), Some(data)) = (free_key, &self.data) {
+                            self.column_presets.push(ColumnPreset {
+                                name: self.temp_preset_name.trim().to_string(),
+                                key,
+                                visible_columns: data.headers.iter()
+                                    .map(|header| self.is_column_visible(header))
+                                    .collect(),
+                            });
+                            self.active_preset = Some(self.column_presets.len() - 1);
+                            self.temp_preset_name.clear();
+                        }
+                    }
+                });
+            });
+        self.show_presets_dialog = open;
+    }
+    
+    // Totals pinned below the scroll area, one per visible column
+    fn total_row_strip(&mut self, ui: &mut egui::Ui, view: &TableView, columns: &[usize]) {
+        if self.column_left.is_empty() {
+            // Nothing recorded yet; come back once the grid has been laid out
+            ui.ctx().request_repaint();
+        }
+        let fill = ui.visuals().extreme_bg_color;
+        egui::TopBottomPanel::bottom("total_row")
+            .frame(egui::Frame::none().fill(fill).inner_margin(egui::Margin::symmetric(0.0, 4.0)))
+            .show_inside(ui, |ui| {
+                let (strip, _) = ui.allocate_exact_size(
+                    egui::vec2(ui.available_width(), view.row_height),
+                    egui::Sense::hover(),
+                );
+                for (index, &col) in columns.iter().enumerate() {
+                    let Some(&left) = self.column_left.get(&col) else {
+                        continue;
+                    };
+                    let right = self.column_left.values()
+                        .copied()
+                        .filter(|&x| x > left)
+                        .fold(strip.right(), f32::min);
+                    if left >= strip.right() || right <= strip.left() {
+                        continue;
+                    }
+                    
+                    let op = self.total_row_ops.get(col).copied().unwrap_or(TotalOp::None);
+                    let values: Vec<f64> = view.rows.iter()
+                        .filter_map(|&row_idx| view.data.rows[row_idx].get(col)?.trim().parse::<f64>().ok())
+                        .collect();
+                    let numeric = self.column_types.get(col).copied().unwrap_or(false);
+                    let mut text = match op.apply(&values) {
+                        _ if !numeric => "—".to_string(),
+                        Some(total) if op == TotalOp::Average => format!("{:.2}", total),
+                        Some(total) => total.to_string(),
+                        None => String::new(),
+                    };
+                    if index == 0 {
+                        text.insert_str(0, "Σ ");
+                    }
+                    
+                    let rect = egui::Rect::from_x_y_ranges(left..=right, strip.y_range());
+                    let response = ui.allocate_ui_at_rect(rect, |ui| {
+                        ui.add(
+                            egui::Label::new(egui::RichText::new(text).strong().size(view.font_size))
+                                .sense(egui::Sense::click()),
+                        )
+                    }).inner;
+                    let response = response.on_hover_text(format!("{} (right-click to change)", op.label()));
+                    response.context_menu(|ui| {
+                        for choice in TotalOp::ALL {
+                            if ui.radio(op == choice, choice.label()).clicked() {
+                                if let Some(slot) = self.total_row_ops.get_mut(col) {
+                                    *slot = choice;
+                                }
+                                ui.close_menu();
+                            }
+                        }
+                    });
                 }
-            },
-            None => {}
+            });
+    }
+    
+    fn column_group(&self, col: usize) -> Option<&ColumnGroup> {
+        self.column_groups.iter().find(|group| group.columns.contains(&col))
+    }
+    
+    // A collapsed group keeps only its first visible column, which then shows the group sum
+    fn collapse_column_groups(&self, columns: &mut Vec<usize>) {
+        let mut seen = HashSet::new();
+        columns.retain(|&col| match self.column_group(col) {
+            Some(group) if self.collapsed_groups.contains(&group.prefix) => seen.insert(group.prefix.clone()),
+            _ => true,
+        });
+    }
+    
+    // Super-header cell above the first visible column of each group; empty elsewhere
+    fn group_header(&mut self, ui: &mut egui::Ui, headers: &[String], columns: &[usize], index: usize, font_size: f32) {
+        let col = columns[index];
+        let Some(group) = self.column_group(col).cloned() else {
+            ui.label("");
+            return;
+        };
+        let starts_group = index == 0 || self.column_group(columns[index - 1]) != Some(&group);
+        if !starts_group {
+            ui.label("");
+            return;
+        }
+        
+        let collapsed = self.collapsed_groups.contains(&group.prefix);
+        let label = if collapsed {
+            format!("⊞ {}", group.prefix)
+        } else {
+            format!("⊟ {}–{}", headers[group.columns.start], headers[group.columns.end - 1])
+        };
+        let response = ui.add(
+            egui::Label::new(egui::RichText::new(label).strong().size(font_size))
+                .sense(egui::Sense::click()),
+        );
+        let hover = if collapsed { "Expand columns" } else { "Collapse into one sum column" };
+        if response.on_hover_text(hover).clicked() {
+            if collapsed {
+                self.collapsed_groups.remove(&group.prefix);
+            } else {
+                self.collapsed_groups.insert(group.prefix);
+            }
         }
     }
     
-    fn open_file_dialog(&mut self) {
-        if let Some(path) = FileDialog::new()
-            .add_filter("CSV Files", &["csv"])
-            .pick_file() {
-            
-            self.file_path = Some(path.clone());
-            self.data_source = Some(DataSource::Local(path.clone()));
-            
-            if let Ok(data) = load_csv_file(&path) {
-                self.data = Some(data);
+    // Sum of a row's numeric cells in a collapsed group, or None if `col` isn't one
+    fn collapsed_group_sum(&self, row: &[String], col: usize) -> Option<String> {
+        let group = self.column_group(col)?;
+        if !self.collapsed_groups.contains(&group.prefix) {
+            return None;
+        }
+        let sum: f64 = group.columns.clone()
+            .filter_map(|c| row.get(c)?.trim().parse::<f64>().ok())
+            .sum();
+        Some(sum.to_string())
+    }
+    
+    // Indices of the rows that pass the filters, in display order
+    fn display_rows(&self, data: &TableData) -> Vec<usize> {
+        let mut rows: Vec<usize> = (0..data.rows.len())
+            .filter(|&row_idx| self.row_passes_filters(data, &data.rows[row_idx]))
+            .collect();
+        if !self.sort_keys.is_empty() {
+            rows.sort_by(|&a, &b| self.compare_rows(data, a, b));
+        }
+        rows
+    }
+    
+    // Visible columns: everything on wide windows, otherwise as many priority levels as fit
+    fn responsive_columns(&self, ui: &egui::Ui, data: &TableData, font_size: f32) -> Vec<usize> {
+        let all_columns: Vec<usize> = (0..data.headers.len())
+            .filter(|&col| self.is_column_visible(&data.headers[col]))
+            .collect();
+        if ui.ctx().screen_rect().width() >= BREAKPOINT_WINDOW_WIDTH {
+            return all_columns;
+        }
+        
+        // Rough width estimate from the longest text in the header and the first rows
+        let char_width = font_size * 0.6;
+        let spacing = ui.spacing().item_spacing.x;
+        let column_width = |col: usize| {
+            let longest = data.rows.iter()
+                .take(50)
+                .filter_map(|row| row.get(col))
+                .chain(std::iter::once(&data.headers[col]))
+                .map(|text| text.chars().count())
+                .max()
+                .unwrap_or(0);
+            longest as f32 * char_width + spacing
+        };
+        
+        let mut levels: Vec<u8> = self.column_priority.clone();
+        levels.sort_unstable();
+        levels.dedup();
+        
+        let available = ui.available_width();
+        let mut max_priority = levels.first().copied().unwrap_or(u8::MAX);
+        for &level in &levels {
+            let required: f32 = all_columns.iter()
+                .filter(|&&col| self.column_priority.get(col).copied().unwrap_or(1) <= level)
+                .map(|&col| column_width(col))
+                .sum();
+            if required > available {
+                break;
             }
+            max_priority = level;
         }
+        
+        all_columns.into_iter()
+            .filter(|&col| self.column_priority.get(col).copied().unwrap_or(1) <= max_priority)
+            .collect()
     }
     
-    fn connect_to_sheet(&mut self) {
-        if !self.temp_url.is_empty() {
-            self.sheet_url = self.temp_url.clone();
-            self.sheet_name = self.temp_sheet.clone();
-            self.data_source = Some(DataSource::Cloud(
-                self.sheet_url.clone(),
-                self.sheet_name.clone()
-            ));
-            
-            if let Ok(data) = load_google_sheet(&self.sheet_url, &self.sheet_name) {
-                self.data = Some(data);
+    fn row_gutter(&mut self, ui: &mut egui::Ui, data: &TableData, row_idx: usize, font_size: f32) {
+        let gutter = ui.horizontal(|ui| {
+            if self.edit_mode {
+                // Moves are by position in the data, which a sorted view doesn't show
+                let grip = ui.add_enabled(
+                    self.sort_keys.is_empty(),
+                    egui::Label::new(egui::RichText::new("⠿").size(font_size))
+                        .sense(egui::Sense::drag()),
+                ).on_disabled_hover_text("Clear the sort to reorder rows");
+                self.handle_row_drag(ui, &grip, row_idx);
+            }
+            if self.locked_rows.contains(&data.row_key(&data.rows[row_idx])) {
+                ui.label(egui::RichText::new("🔒").size(font_size))
+                    .on_hover_text("Locked — not updated by auto-refresh");
+            }
+        });
+        self.main_row_centers.push((row_idx, gutter.response.rect.center().y));
+    }
+    
+    fn header_context_menu(&mut self, ui: &mut egui::Ui, view: &TableView, col: usize) {
+        if ui.button("Copy All Values").clicked() {
+            // Only the rows currently displayed, in display order
+            let values: Vec<String> = view.rows.iter()
+                .map(|&row_idx| {
+                    let row = &view.data.rows[row_idx];
+                    self.collapsed_group_sum(row, col)
+                        .unwrap_or_else(|| row.get(col).cloned().unwrap_or_default())
+                })
+                .collect();
+            ui.output_mut(|o| o.copied_text = values.join("\n"));
+            self.show_status(format!("Copied {} values", values.len()));
+            ui.close_menu();
+        }
+    }
+    
+    // Click selects one row, Ctrl+Click toggles a row, Shift+Click selects a range in display order
+    fn click_row(&mut self, display_rows: &[usize], row_idx: usize, modifiers: egui::Modifiers) {
+        let anchor_pos = self.selection_anchor
+            .and_then(|anchor| display_rows.iter().position(|&r| r == anchor));
+        let clicked_pos = display_rows.iter().position(|&r| r == row_idx);
+        if let (true, Some(from), Some(to)) = (modifiers.shift, anchor_pos, clicked_pos) {
+            if !modifiers.command {
+                self.selected_rows.clear();
+            }
+            self.selected_rows.extend(&display_rows[from.min(to)..=from.max(to)]);
+            return;
+        }
+        
+        if modifiers.command {
+            if !self.selected_rows.remove(&row_idx) {
+                self.selected_rows.insert(row_idx);
             }
+        } else if self.selected_rows.len() == 1 && self.selected_rows.contains(&row_idx) {
+            self.selected_rows.clear();
+        } else {
+            self.selected_rows.clear();
+            self.selected_rows.insert(row_idx);
         }
+        self.selection_anchor = Some(row_idx);
     }
     
-    fn display_table(&self, ui: &mut egui::Ui, data: &TableData) {
-        egui::ScrollArea::both().show(ui, |ui| {
-            // Table with headers and data rows
-            egui::Grid::new("data_grid")
-                .striped(true)
-                .show(ui, |ui| {
-                    // Headers
-                    for header in &data.headers {
-                        ui.strong(header);
+    fn cell_context_menu(&mut self, ui: &mut egui::Ui, data: &TableData, row_idx: usize, col: usize) {
+        // Row actions apply to the whole selection when opened on a selected row
+        let mut targets: Vec<usize> = if self.selected_rows.contains(&row_idx) {
+            self.selected_rows.iter().copied().collect()
+        } else {
+            vec![row_idx]
+        };
+        targets.sort_unstable();
+        let keys: Vec<String> = targets.iter().map(|&r| data.row_key(&data.rows[r])).collect();
+        let plural = if targets.len() > 1 { "s" } else { "" };
+        
+        if ui.button("View History").clicked() {
+            self.history_cell = Some((data.row_key(&data.rows[row_idx]), data.headers[col].clone()));
+            ui.close_menu();
+        }
+        let copy_label = if targets.len() > 1 { "Copy Selected Rows" } else { "Copy Row" };
+        if ui.button(copy_label).clicked() {
+            let text = targets.iter()
+                .map(|&r| data.rows[r].join("\t"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            ui.output_mut(|o| o.copied_text = text);
+            self.show_status(format!("Copied {} row{}", targets.len(), plural));
+            ui.close_menu();
+        }
+        if keys.iter().all(|key| self.locked_rows.contains(key)) {
+            if ui.button(format!("Unlock Row{}", plural)).clicked() {
+                for key in &keys {
+                    self.locked_rows.remove(key);
+                }
+                ui.close_menu();
+            }
+        } else if ui.button(format!("Lock Row{}", plural)).clicked() {
+            self.locked_rows.extend(keys.iter().cloned());
+            ui.close_menu();
+        }
+        if let [first, second] = keys.as_slice() {
+            if ui.button("Compare Rows").clicked() {
+                self.compared_rows = Some((first.clone(), second.clone()));
+                ui.close_menu();
+            }
+        }
+        if ui.button(format!("Hide Row{}", plural)).clicked() {
+            self.hidden_rows.extend(keys);
+            self.selected_rows.clear();
+            ui.close_menu();
+        }
+        
+        if !self.custom_row_actions.is_empty() {
+            ui.separator();
+            let rows: Vec<Vec<String>> = targets.iter().map(|&r| data.rows[r].clone()).collect();
+            for action in &self.custom_row_actions {
+                if ui.button(&action.label).clicked() {
+                    (action.callback)(&rows);
+                    ui.close_menu();
+                }
+            }
+        }
+    }
+    
+    fn handle_row_drag(&mut self, ui: &egui::Ui, grip: &egui::Response, row_idx: usize) {
+        if grip.drag_started() {
+            self.drag_row = Some(row_idx);
+            self.drop_target_row = Some(row_idx);
+        }
+        
+        if self.drag_row.is_some() {
+            let row_span = grip.rect.expand2(egui::vec2(0.0, ui.spacing().item_spacing.y / 2.0)).y_range();
+            if let Some(pointer) = ui.input(|i| i.pointer.hover_pos()) {
+                if row_span.contains(&pointer.y) {
+                    self.drop_target_row = Some(row_idx);
+                }
+            }
+            if self.drop_target_row == Some(row_idx) && self.drag_row != Some(row_idx) {
+                let stroke = egui::Stroke::new(2.0, ui.visuals().selection.stroke.color);
+                ui.painter().hline(grip.rect.left()..=ui.clip_rect().right(), *row_span.start(), stroke);
+            }
+        }
+        
+        if grip.drag_released() {
+            let (from, target) = (self.drag_row.take(), self.drop_target_row.take());
+            let pointer = ui.input(|i| i.pointer.interact_pos());
+            let split_rows = self.split_data.as_ref().map_or(0, |split| split.rows.len());
+            let split_target = pointer
+                .and_then(|pos| drop_index(self.split_table_rect, &self.split_row_centers, split_rows, pos));
+            match (from, split_target, target) {
+                (Some(from), Some(to), _) => self.pending_split_move = Some(SplitMove { to_split: true, from, to }),
+                (Some(from), None, Some(to)) if from != to => self.pending_row_move = Some((from, to)),
+                _ => {}
+            }
+        }
+    }
+    
+    // Dropping a header on another one swaps the two columns
+    fn handle_header_drag(&mut self, ui: &egui::Ui, header: &egui::Response, col: usize) {
+        if header.drag_started() {
+            self.drag_column = Some(col);
+            self.drop_target_column = Some(col);
+        }
+        
+        if self.drag_column.is_some() {
+            let column_span = header.rect.expand2(egui::vec2(ui.spacing().item_spacing.x / 2.0, 0.0)).x_range();
+            if let Some(pointer) = ui.input(|i| i.pointer.hover_pos()) {
+                if column_span.contains(&pointer.x) {
+                    self.drop_target_column = Some(col);
+                }
+            }
+            if self.drop_target_column == Some(col) && self.drag_column != Some(col) {
+                let stroke = egui::Stroke::new(2.0, ui.visuals().selection.stroke.color);
+                ui.painter().rect_stroke(header.rect.expand(2.0), 2.0, stroke);
+            }
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+        }
+        
+        if header.drag_released() {
+            match (self.drag_column.take(), self.drop_target_column.take()) {
+                (Some(from), Some(to)) if from != to => self.pending_column_swap = Some((from, to)),
+                _ => {}
+            }
+        }
+    }
+    
+    fn apply_pending_column_swap(&mut self) {
+        let Some((from, to)) = self.pending_column_swap.take() else {
+            return;
+        };
+        let Some(data) = &self.data else {
+            return;
+        };
+        let mut order: Vec<usize> = (0..data.headers.len()).collect();
+        order.swap(from, to);
+        self.apply_column_order(&order);
+        self.save_column_config();
+    }
+    
+    // Back to the order the source has the columns in
+    fn reset_column_order(&mut self) {
+        let mut order: Vec<usize> = (0..self.column_order.len()).collect();
+        order.sort_by_key(|&position| self.column_order[position]);
+        self.apply_column_order(&order);
+        self.save_column_config();
+    }
+    
+    // Second table beside the main one; in edit mode its rows can be dragged across
+    fn split_view_panel(&mut self, ctx: &egui::Context) {
+        egui::SidePanel::right("split_view")
+            .resizable(true)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                self.split_table_rect = Some(ui.max_rect());
+                self.split_row_centers.clear();
+                ui.horizontal(|ui| {
+                    ui.strong(&self.split_label);
+                    if ui.small_button("✕").on_hover_text("Close split view").clicked() {
+                        self.split_data = None;
+                    }
+                });
+                let Some(split) = self.split_data.take() else {
+                    return;
+                };
+                let font_size = self.font_size;
+                egui::ScrollArea::both().id_source("split_view_scroll").show(ui, |ui| {
+                    egui::Grid::new("split_grid")
+                        .striped(true)
+                        .min_row_height(self.row_height)
+                        .show(ui, |ui| {
+                            ui.label("");
+                            for header in &split.headers {
+                                ui.label(egui::RichText::new(header).strong().size(font_size));
+                            }
+                            ui.end_row();
+                            for (row_idx, row) in split.rows.iter().enumerate() {
+                                let sense = if self.edit_mode { egui::Sense::drag() } else { egui::Sense::hover() };
+                                let grip = ui.add(egui::Label::new(egui::RichText::new("⠿").size(font_size)).sense(sense));
+                                self.split_row_centers.push((row_idx, grip.rect.center().y));
+                                self.handle_split_drag(ui, &grip, row_idx);
+                                for cell in row {
+                                    ui.label(egui::RichText::new(cell).size(font_size));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+                self.split_data = Some(split);
+            });
+    }
+    
+    fn handle_split_drag(&mut self, ui: &egui::Ui, grip: &egui::Response, row_idx: usize) {
+        if grip.drag_started() {
+            self.split_drag_row = Some(row_idx);
+        }
+        if grip.drag_released() {
+            let main_rows = self.data.as_ref().map_or(0, |data| data.rows.len());
+            let target = ui.input(|i| i.pointer.interact_pos())
+                .and_then(|pos| drop_index(self.main_table_rect, &self.main_row_centers, main_rows, pos));
+            if let (Some(from), Some(to)) = (self.split_drag_row.take(), target) {
+                self.pending_split_move = Some(SplitMove { to_split: false, from, to });
+            }
+        }
+    }
+    
+    fn apply_split_move(&mut self, split_move: SplitMove) {
+        let (Some(data), Some(split)) = (self.data.as_mut(), self.split_data.as_mut()) else {
+            return;
+        };
+        let source_rows = if split_move.to_split { data.rows.len() } else { split.rows.len() };
+        if split_move.from >= source_rows {
+            return;
+        }
+        push_undo(&mut self.undo_history, UndoSnapshot { data: data.clone(), split_data: Some(split.clone()) });
+        let (source, target) = if split_move.to_split { (data, split) } else { (split, data) };
+        let row = source.rows.remove(split_move.from);
+        
+        // Line the cells up with the target's columns by name; tables with no shared columns take the row as is
+        let shares_columns = target.headers.iter().any(|header| source.headers.contains(header));
+        let row = if shares_columns {
+            target.headers.iter()
+                .map(|header| source.headers.iter()
+                    .position(|h| h == header)
+                    .and_then(|col| row.get(col))
+                    .cloned()
+                    .unwrap_or_default())
+                .collect()
+        } else {
+            row
+        };
+        target.rows.insert(split_move.to.min(target.rows.len()), row);
+        self.selected_rows.clear();
+    }
+    
+    fn apply_pending_row_move(&mut self) {
+        if let (Some((from, to)), Some(data)) = (self.pending_row_move.take(), self.data.as_mut()) {
+            // The rows may have changed since the drag started
+            if from >= data.rows.len() || to >= data.rows.len() {
+                return;
+            }
+            push_undo(&mut self.undo_history, UndoSnapshot { data: data.clone(), split_data: self.split_data.clone() });
+            let row = data.rows.remove(from);
+            data.rows.insert(to, row);
+            // Selection is by row index, which the move has shifted
+            self.selected_rows.clear();
+        }
+    }
+    
+    fn undo(&mut self) {
+        if let Some(previous) = self.undo_history.pop() {
+            self.data = Some(previous.data);
+            if previous.split_data.is_some() {
+                self.split_data = previous.split_data;
+            }
+        }
+    }
+    
+    fn row_passes_filters(&self, data: &TableData, row: &[String]) -> bool {
+        if !self.hidden_rows.is_empty() || !self.auto_hidden_rows.is_empty() {
+            let key = data.row_key(row);
+            if self.hidden_rows.contains(&key) || self.auto_hidden_rows.contains(&key) {
+                return false;
+            }
+        }
+        if let Some(expr) = &self.filter_expr {
+            if !expr.matches(row) {
+                return false;
+            }
+        }
+        let needle = self.filter_text.trim().to_lowercase();
+        if !needle.is_empty() && !row.iter().any(|cell| cell.to_lowercase().contains(&needle)) {
+            return false;
+        }
+        if !self.selected_categories.is_empty() {
+            let category = self.category_column(data).and_then(|col| row.get(col));
+            if !category.is_some_and(|category| self.selected_categories.contains(category.trim())) {
+                return false;
+            }
+        }
+        true
+    }
+    
+    // Placeholder grid with a sweeping shimmer, shown while data is being fetched
+    fn loading_skeleton(&self, ui: &mut egui::Ui) {
+        let headers: Vec<String> = match &self.data {
+            Some(data) => data.headers.clone(),
+            None => vec![String::new(); SKELETON_COLUMNS],
+        };
+        let time = ui.input(|i| i.time) as f32;
+        let dark = ui.visuals().faint_bg_color;
+        let light = ui.visuals().widgets.inactive.bg_fill;
+        let cell_size = egui::vec2(self.font_size * 5.0, self.font_size * 0.8);
+        
+        egui::Grid::new("loading_skeleton")
+            .min_row_height(self.row_height)
+            .show(ui, |ui| {
+                for header in &headers {
+                    ui.label(egui::RichText::new(header).strong().size(self.font_size));
+                }
+                ui.end_row();
+                for row in 0..SKELETON_ROWS {
+                    for col in 0..headers.len() {
+                        let (rect, _) = ui.allocate_exact_size(cell_size, egui::Sense::hover());
+                        let phase = time * 3.0 - (row + col) as f32 * 0.3;
+                        let t = (phase.sin() + 1.0) / 2.0;
+                        ui.painter().rect_filled(rect, 2.0, lerp_color(dark, light, t));
                     }
                     ui.end_row();
-                    
-                    // Data rows
-                    for row in &data.rows {
-                        for cell in row {
-                            ui.label(cell);
+                }
+            });
+        ui.ctx().request_repaint();
+    }
+    
+    fn cell_background(&self, row_idx: usize, col: usize, cell: &str) -> Option<egui::Color32> {
+        // Color-only cues are turned off in accessibility mode
+        if self.accessibility_mode {
+            return None;
+        }
+        
+        if self.search_focus.and_then(|focus| self.search_matches.get(focus)) == Some(&(row_idx, col)) {
+            return Some(SEARCH_FOCUS_COLOR);
+        }
+        if self.is_search_match(cell) {
+            return Some(SEARCH_MATCH_COLOR);
+        }
+        
+        // Recently changed cells fade from the highlight color back to the normal stripe
+        let changed_at = self.cell_change_times.get(&(row_idx, col))?;
+        let t = changed_at.elapsed().as_secs_f32() / CHANGE_FLASH_DURATION.as_secs_f32();
+        (t < 1.0).then(|| CHANGE_FLASH_COLOR.linear_multiply(1.0 - t))
+    }
+    
+    // Bar that is full width for rank 1 and shrinks as 1 / rank, fading from gold to gray
+    fn rank_bar(ui: &mut egui::Ui, rank: f64, max_rank: f64, view: &TableView) -> egui::Response {
+        let size = egui::vec2(view.font_size * 6.0, view.row_height * 0.6);
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+        let fraction = (1.0 / rank) as f32;
+        let t = if max_rank > 1.0 { ((rank - 1.0) / (max_rank - 1.0)) as f32 } else { 0.0 };
+        let bar = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width() * fraction, rect.height()));
+        ui.painter().rect_filled(bar, 2.0, lerp_color(RANK_BAR_GOLD, RANK_BAR_GRAY, t));
+        response.on_hover_text(format!("Rank {}", rank))
+    }
+    
+    // Color-only, so off in accessibility mode
+    fn row_highlight(&self, row: &[String]) -> Option<egui::Color32> {
+        if self.accessibility_mode {
+            return None;
+        }
+        self.highlight_rules.iter()
+            .find(|rule| {
+                let cell = row.get(rule.column_index).map_or("", String::as_str);
+                !cell.trim().is_empty() && rule.comparator.holds(compare_values(cell, &rule.threshold))
+            })
+            .map(|rule| rule.color)
+    }
+    
+    // Did not start, did not finish, disqualified and the like
+    fn is_dnx_row(&self, row: &[String]) -> bool {
+        row.iter().any(|cell| {
+            let cell = cell.trim();
+            self.settings.dnx_patterns.iter().any(|pattern| cell.eq_ignore_ascii_case(pattern))
+        })
+    }
+    
+    // `with_gutter` adds a leading column for row drag handles and lock icons
+    fn table_grid(&mut self, ui: &mut egui::Ui, id_source: &str, view: &TableView, columns: &[usize], with_gutter: bool) {
+        let data = view.data;
+        let font_size = view.font_size;
+        let result_col = data.headers.iter().position(|header| header == RESULT_COLUMN);
+        let rank_col = data.headers.iter()
+            .position(|header| RANK_COLUMNS.iter().any(|name| header.eq_ignore_ascii_case(name)))
+            .filter(|_| self.show_rank_bars);
+        let max_rank = rank_col
+            .and_then(|col| data.numeric_values(col))
+            .map_or(1.0, |ranks| ranks.into_iter().fold(1.0, f64::max));
+        
+        // Table with headers and data rows
+        egui::Grid::new(id_source)
+            .striped(true)
+            .min_row_height(view.row_height)
+            .show(ui, |ui| {
+                // Group super-headers
+                if columns.iter().any(|&col| self.column_group(col).is_some()) {
+                    if with_gutter {
+                        ui.label("");
+                    }
+                    for index in 0..columns.len() {
+                        self.group_header(ui, &data.headers, columns, index, font_size);
+                    }
+                    ui.end_row();
+                }
+                
+                // Headers
+                if with_gutter {
+                    ui.label("");
+                }
+                for &col in columns {
+                    let mut text = match self.column_group(col) {
+                        Some(group) if self.collapsed_groups.contains(&group.prefix) => format!("{} Σ", group.prefix),
+                        _ => data.headers[col].clone(),
+                    };
+                    if let Some(position) = self.sort_keys.iter().position(|&(key, _)| key == col) {
+                        text.push_str(if self.sort_keys[position].1 { " ▼" } else { " ▲" });
+                        if self.sort_keys.len() > 1 {
+                            text.push_str(&superscript(position + 1));
                         }
-                        ui.end_row();
                     }
-                });
+                    let header = ui.add(
+                        egui::Label::new(egui::RichText::new(text).strong().size(font_size))
+                            .sense(egui::Sense::click_and_drag()),
+                    );
+                    self.column_left.insert(col, header.rect.left());
+                    self.handle_header_drag(ui, &header, col);
+                    let header = header.on_hover_ui(|ui| {
+                        ui.label(format!(
+                            "[Type: {}] Sample: {}",
+                            data.column_type(col).label(),
+                            data.sample_values(col, HEADER_SAMPLE_VALUES).join(", "),
+                        ));
+                    });
+                    if header.clicked() {
+                        let shift = ui.input(|i| i.modifiers.shift);
+                        self.cycle_header_sort(col, shift);
+                    }
+                    header.context_menu(|ui| self.header_context_menu(ui, view, col));
+                }
+                ui.end_row();
+                
+                // Data rows
+                for &row_idx in &view.rows {
+                    let row = &data.rows[row_idx];
+                    let struck = self.show_dnx_strikethrough && self.is_dnx_row(row);
+                    let highlight = self.row_highlight(row);
+                    if with_gutter {
+                        self.row_gutter(ui, data, row_idx, font_size);
+                    }
+                    for &col in columns {
+                        let raw_cell = row.get(col).map_or("", String::as_str);
+                        let numeric = self.column_types.get(col).copied().unwrap_or(false);
+                        let formatted = match (self.collapsed_group_sum(row, col), self.result_decimals) {
+                            (Some(sum), _) => Cow::Owned(sum),
+                            (None, Some(decimals)) if Some(col) == result_col => format_decimals(raw_cell, decimals),
+                            _ if numeric => format_whole_number(raw_cell),
+                            _ => Cow::Borrowed(raw_cell),
+                        };
+                        let cell = formatted.as_ref();
+                        let background = ui.painter().add(egui::Shape::Noop);
+                        let rank = cell.trim().parse::<f64>().ok().filter(|&rank| rank >= 1.0);
+                        let response = match rank {
+                            Some(rank) if Some(col) == rank_col => Self::rank_bar(ui, rank, max_rank, view),
+                            _ => {
+                                let mut text = egui::RichText::new(cell).size(font_size);
+                                if struck {
+                                    text = text.strikethrough();
+                                }
+                                let label = egui::Label::new(text).sense(egui::Sense::click());
+                                if numeric {
+                                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| ui.add(label)).inner
+                                } else {
+                                    ui.add(label)
+                                }
+                            },
+                        };
+                        if response.clicked() {
+                            let modifiers = ui.input(|i| i.modifiers);
+                            self.click_row(&view.rows, row_idx, modifiers);
+                        }
+                        let response = response.context_menu(|ui| self.cell_context_menu(ui, data, row_idx, col));
+                        if self.accessibility_mode {
+                            // Give screen readers the row and column context of every cell
+                            let description = format!("Row {}, {}: {}", row_idx + 1, data.headers[col], cell);
+                            response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Label, &description));
+                        }
+                        let annotated = self.annotations.iter().any(|a| a.row == row_idx && a.col == col);
+                        if annotated && ui.clip_rect().intersects(response.rect) {
+                            self.annotation_cells.insert((row_idx, col), response.rect);
+                        }
+                        let focused = self.search_focus.and_then(|focus| self.search_matches.get(focus)) == Some(&(row_idx, col));
+                        if focused && self.scroll_to_search_focus {
+                            response.scroll_to_me(Some(egui::Align::Center));
+                            self.scroll_to_search_focus = false;
+                        }
+                        if self.scroll_to_row == Some(row_idx) {
+                            ui.scroll_to_rect(response.rect, Some(egui::Align::Center));
+                            self.scroll_to_row = None;
+                        }
+                        let selected = self.selected_rows.contains(&row_idx).then(|| ui.visuals().selection.bg_fill);
+                        if let Some(color) = self.cell_background(row_idx, col, raw_cell).or(selected).or(highlight) {
+                            ui.painter().set(background, egui::Shape::rect_filled(response.rect.expand(2.0), 2.0, color));
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+// Per category, how many athletes have a score in every stage column (S1, S2, ...) out of how many
+// there are; empty when the table has no stage columns
+fn stage_progress(data: &TableData, category_col: usize) -> HashMap<&str, (usize, usize)> {
+    let is_stage = |header: &str| {
+        let number = header.strip_prefix(['S', 's']).unwrap_or("");
+        !number.is_empty() && number.chars().all(|c| c.is_ascii_digit())
+    };
+    let stages: Vec<usize> = (0..data.headers.len()).filter(|&col| is_stage(&data.headers[col])).collect();
+    let mut progress = HashMap::new();
+    if stages.is_empty() {
+        return progress;
+    }
+    for row in &data.rows {
+        let Some(category) = row.get(category_col).map(|cell| cell.trim()).filter(|cell| !cell.is_empty()) else {
+            continue;
+        };
+        let complete = stages.iter().all(|&col| row.get(col).is_some_and(|cell| !cell.trim().is_empty()));
+        let entry = progress.entry(category).or_insert((0, 0));
+        entry.0 += complete as usize;
+        entry.1 += 1;
+    }
+    progress
+}
+
+// Histogram with a normal curve of the same mean and standard deviation, scaled to the bar heights
+fn distribution_plot(ui: &mut egui::Ui, values: &[f64], stats: &ColumnStatistics, bins: usize) {
+    use egui::plot::{Bar, BarChart, Line, Plot, PlotPoints};
+    
+    let range = stats.max - stats.min;
+    let bin_width = if range > 0.0 { range / bins as f64 } else { 1.0 };
+    let mut counts = vec![0usize; bins];
+    for value in values {
+        let bin = ((value - stats.min) / bin_width) as usize;
+        counts[bin.min(bins - 1)] += 1;
+    }
+    let bars: Vec<Bar> = counts.iter().enumerate()
+        .map(|(bin, &count)| Bar::new(stats.min + (bin as f64 + 0.5) * bin_width, count as f64).width(bin_width * 0.95))
+        .collect();
+    
+    let curve = (stats.stddev > 0.0).then(|| {
+        let scale = values.len() as f64 * bin_width / (stats.stddev * (2.0 * std::f64::consts::PI).sqrt());
+        let (start, end) = (stats.mean - 3.0 * stats.stddev, stats.mean + 3.0 * stats.stddev);
+        let points: PlotPoints = (0..=NORMAL_CURVE_POINTS)
+            .map(|i| {
+                let x = start + (end - start) * i as f64 / NORMAL_CURVE_POINTS as f64;
+                let z = (x - stats.mean) / stats.stddev;
+                [x, scale * (-0.5 * z * z).exp()]
+            })
+            .collect();
+        Line::new(points).name("Normal").color(ui.visuals().warn_fg_color)
+    });
+    
+    Plot::new("distribution_plot")
+        .allow_scroll(false)
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(BarChart::new(bars).name("Count"));
+            if let Some(curve) = curve {
+                plot_ui.line(curve);
+            }
         });
+}
+
+// Each line is "<column> [ASC|DESC]"; columns match case-insensitively, exact names before prefixes
+fn parse_sort_spec(spec: &str, headers: &[String]) -> Result<Vec<(usize, bool)>, Vec<String>> {
+    let mut keys = Vec::new();
+    let mut errors = Vec::new();
+    
+    for line in spec.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let (name, descending) = match line.rsplit_once(char::is_whitespace) {
+            Some((name, direction)) if direction.eq_ignore_ascii_case("desc") => (name.trim(), true),
+            Some((name, direction)) if direction.eq_ignore_ascii_case("asc") => (name.trim(), false),
+            _ => (line, false),
+        };
+        let name_lower = name.to_lowercase();
+        let column = headers.iter()
+            .position(|header| header.to_lowercase() == name_lower)
+            .or_else(|| headers.iter().position(|header| header.to_lowercase().starts_with(&name_lower)));
+        match column {
+            Some(col) => keys.push((col, descending)),
+            None => errors.push(format!("Unknown column '{}'", name)),
+        }
+    }
+    
+    if errors.is_empty() { Ok(keys) } else { Err(errors) }
+}
+
+// Numbers are shown with exactly `decimals` places; anything else is left alone
+fn format_decimals(cell: &str, decimals: usize) -> Cow<'_, str> {
+    match cell.trim().parse::<f64>() {
+        Ok(value) => Cow::Owned(format!("{:.*}", decimals, value)),
+        Err(_) => Cow::Borrowed(cell),
+    }
+}
+
+// Sort priority shown after a header, like "▲²"
+fn superscript(value: usize) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    value.to_string()
+        .chars()
+        .filter_map(|digit| digit.to_digit(10))
+        .map(|digit| DIGITS[digit as usize])
+        .collect()
+}
+
+// Whole numbers written with a fraction, like "98.0", are shown as "98"
+fn format_whole_number(cell: &str) -> Cow<'_, str> {
+    let trimmed = cell.trim();
+    match trimmed.parse::<f64>() {
+        Ok(value) if trimmed.contains('.') && value.fract() == 0.0 && value.abs() < 1e15 => Cow::Owned(format!("{}", value as i64)),
+        _ => Cow::Borrowed(cell),
+    }
+}
+
+// Black-on-white QR code, one pixel per module, with the standard quiet zone
+fn qr_code_image(text: &str) -> Result<egui::ColorImage, qrcode::types::QrError> {
+    let code = qrcode::QrCode::new(text.as_bytes())?;
+    let width = code.width();
+    let size = width + 2 * QR_QUIET_ZONE;
+    let mut image = egui::ColorImage::new([size, size], egui::Color32::WHITE);
+    for (index, color) in code.to_colors().into_iter().enumerate() {
+        if color == qrcode::Color::Dark {
+            let (x, y) = (index % width + QR_QUIET_ZONE, index / width + QR_QUIET_ZONE);
+            image.pixels[y * size + x] = egui::Color32::BLACK;
+        }
+    }
+    Ok(image)
+}
+
+fn data_age_color(age: Duration) -> egui::Color32 {
+    if age < DATA_AGE_FRESH {
+        egui::Color32::from_rgb(40, 160, 60)
+    } else if age < DATA_AGE_STALE {
+        egui::Color32::from_rgb(210, 170, 20)
+    } else {
+        egui::Color32::from_rgb(200, 40, 40)
+    }
+}
+
+// 42000 -> "42 000"
+fn format_thousands(value: usize) -> String {
+    let digits: Vec<char> = value.to_string().chars().collect();
+    digits.rchunks(3)
+        .rev()
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn lerp_color(from: egui::Color32, to: egui::Color32, t: f32) -> egui::Color32 {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    egui::Color32::from_rgba_premultiplied(
+        channel(from.r(), to.r()),
+        channel(from.g(), to.g()),
+        channel(from.b(), to.b()),
+        channel(from.a(), to.a()),
+    )
+}
+
+fn push_undo(history: &mut Vec<UndoSnapshot>, snapshot: UndoSnapshot) {
+    if history.len() >= UNDO_HISTORY_LIMIT {
+        history.remove(0);
+    }
+    history.push(snapshot);
+}
+
+// Row index a drop at `pointer` inserts before, or None if the pointer is outside `rect`
+fn drop_index(rect: Option<egui::Rect>, row_centers: &[(usize, f32)], row_count: usize, pointer: egui::Pos2) -> Option<usize> {
+    if !rect?.contains(pointer) {
+        return None;
+    }
+    Some(row_centers.iter()
+        .find(|&&(_, center)| center > pointer.y)
+        .map_or(row_count, |&(row_idx, _)| row_idx))
+}
+
+// 1 = always shown, higher numbers are hidden first on narrow windows
+fn default_column_priority(header: &str) -> u8 {
+    match header {
+        "Name" | "Surname" | "Result" | "Rank" => 1,
+        "Series" | "X" => 2,
+        _ if header.starts_with('P') && header[1..].parse::<u32>().is_ok() => 2,
+        "Club" => 3,
+        _ if header.starts_with('S') && header[1..].parse::<u32>().is_ok() => 3,
+        _ => 2,
+    }
+}
+
+// Login name shown on comments when no name is set
+fn comment_username_fallback() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "Operator".to_string())
+}
+
+fn file_modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+// Command-line options
+struct LaunchArgs {
+    source: Option<DataSource>,
+    readonly_pin: Option<String>,
+    compact: bool,
+}
+
+// Command line options: --file <path>, --stdin (same as --file -), --url <csv url> or
+// --sheet-url <url> [--sheet-name <name>], plus --pin <PIN> (or SCORE_VIEWER_PIN in the
+// environment) for read-only mode and --compact for a table-only window
+fn parse_args() -> LaunchArgs {
+    let mut file = None;
+    let mut sheet_url = None;
+    let mut csv_url = None;
+    let mut sheet_name = String::new();
+    let mut link_source = None;
+    let mut readonly_pin = std::env::var(PIN_ENV_VAR).ok().filter(|pin| !pin.is_empty());
+    // Not passed on to anything this process starts, such as xdg-mime
+    std::env::remove_var(PIN_ENV_VAR);
+    let mut compact = false;
+    
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--file" => file = args.next().map(PathBuf::from),
+            "--stdin" => file = Some(PathBuf::from("-")),
+            "--url" => csv_url = args.next(),
+            "--sheet-url" => sheet_url = args.next(),
+            "--sheet-name" => sheet_name = args.next().unwrap_or_default(),
+            "--pin" => readonly_pin = args.next().filter(|pin| !pin.is_empty()),
+            "--compact" => compact = true,
+            link if link.starts_with(url_scheme::URL_SCHEME) => match url_scheme::parse_link(link) {
+                Some(source) => link_source = Some(source),
+                None => app_log::log(format!("Ignoring invalid link: {}", link)),
+            },
+            _ => app_log::log(format!("Ignoring unknown argument: {}", arg)),
+        }
     }
+    
+    let source = link_source.or(match (file, csv_url, sheet_url) {
+        (Some(path), _, _) if is_xlsx_path(&path) => Some(DataSource::Workbook(path, 0)),
+        (Some(path), _, _) => Some(DataSource::Local(path)),
+        (None, Some(url), _) => Some(DataSource::Url(url)),
+        (None, None, Some(url)) => Some(DataSource::Cloud(url, sheet_name)),
+        (None, None, None) => None,
+    });
+    LaunchArgs { source, readonly_pin, compact }
 }
 
 fn main() -> Result<(), eframe::Error> {
+    // A link opened while the viewer is already running goes to that instance
+    let link = std::env::args().skip(1).find(|arg| arg.starts_with(url_scheme::URL_SCHEME));
+    let instance_listener = url_scheme::claim_instance();
+    if let (Some(link), None) = (&link, &instance_listener) {
+        if url_scheme::forward_to_running_instance(link) {
+            return Ok(());
+        }
+    }
+    
+    let args = parse_args();
+    // Settings first, so the column layout saved for the source applies to the initial load.
+    // Without a source on the command line, reopen the one from the last session; if it has
+    // gone missing the load error is shown in place of the table.
+    let settings = Settings::load();
+    let window_size = settings.window_size.map_or(DEFAULT_WINDOW_SIZE, |[width, height]| egui::vec2(width, height));
+    let window_position = settings.window_position.map(|[x, y]| egui::pos2(x, y));
+    let dark_theme = settings.dark_theme;
+    let source = args.source.or_else(|| settings.last_source.as_ref().map(SavedSource::to_source));
+    let mut viewer = match source {
+        Some(source) => ScoreViewer::with_data_source(source, settings),
+        None => ScoreViewer { settings, ..ScoreViewer::default() },
+    };
+    viewer.theme_is_dark = dark_theme;
+    viewer.readonly_pin = args.readonly_pin;
+    viewer.compact_mode = args.compact;
+    viewer.compact_locked = args.compact;
+    
     let options = eframe::NativeOptions {
-        initial_window_size: Some(egui::vec2(1024.0, 768.0)),
+        initial_window_size: Some(window_size),
+        initial_window_pos: window_position,
+        // eframe 0.22 only takes the icon here and can't change it once the window exists, so
+        // it shows the table as first loaded; sources still loading get the default icon
+        icon_data: viewer.data.as_ref().map(thumbnail::table_thumbnail),
+        drag_and_drop_support: true,
         ..Default::default()
     };
     
     eframe::run_native(
         "Score Viewer",
         options,
-        Box::new(|_cc: &CreationContext| Box::new(ScoreViewer::default()))
+        Box::new(|cc: &CreationContext| {
+            fonts::install_fallback_font(&cc.egui_ctx);
+            viewer.forwarded_links = instance_listener
+                .map(|listener| url_scheme::listen_for_links(listener, cc.egui_ctx.clone()));
+            Box::new(viewer)
+        })
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn headers() -> Vec<String> {
+        ["Name", "Surname", "Result", "Result Time"].iter().map(|header| header.to_string()).collect()
+    }
+    
+    #[test]
+    fn sort_spec_reads_directions() {
+        assert_eq!(parse_sort_spec("Result DESC\nName\nSurname asc", &headers()), Ok(vec![(2, true), (0, false), (1, false)]));
+    }
+    
+    #[test]
+    fn sort_spec_matches_exact_names_before_prefixes() {
+        assert_eq!(parse_sort_spec("result", &headers()), Ok(vec![(2, false)]));
+        assert_eq!(parse_sort_spec("SUR desc", &headers()), Ok(vec![(1, true)]));
+        assert_eq!(parse_sort_spec("Result Time DESC", &headers()), Ok(vec![(3, true)]));
+    }
+    
+    #[test]
+    fn sort_spec_skips_blank_lines() {
+        assert_eq!(parse_sort_spec("\n  \nName\n", &headers()), Ok(vec![(0, false)]));
+        assert_eq!(parse_sort_spec("", &headers()), Ok(Vec::new()));
+    }
+    
+    #[test]
+    fn sort_spec_reports_every_unknown_column() {
+        assert_eq!(
+            parse_sort_spec("Age\nName\nClub DESC", &headers()),
+            Err(vec!["Unknown column 'Age'".to_string(), "Unknown column 'Club'".to_string()]),
+        );
+    }
+}
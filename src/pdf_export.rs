@@ -0,0 +1,173 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use printpdf::image_crate::{self, DynamicImage};
+use printpdf::{Image, ImageTransform, IndirectFontRef, Line, Mm, PdfDocument, PdfLayerReference, Point, TextRenderingMode};
+use crate::fonts::FALLBACK_FONT;
+use crate::settings::PrintHeader;
+
+// A4 landscape, in millimetres
+const PAGE_WIDTH: f32 = 297.0;
+const PAGE_HEIGHT: f32 = 210.0;
+const MARGIN: f32 = 15.0;
+const LOGO_HEIGHT: f32 = 16.0;
+const ROW_HEIGHT: f32 = 5.5;
+
+// Font sizes in points
+const TITLE_SIZE: f32 = 16.0;
+const SUBTITLE_SIZE: f32 = 11.0;
+const CELL_SIZE: f32 = 9.0;
+
+const MM_PER_POINT: f32 = 25.4 / 72.0;
+// Average DejaVu Sans glyph width relative to the font size
+const CHAR_WIDTH: f32 = 0.55;
+// Outline width, in points, that thickens regular glyphs into bold ones
+const BOLD_STROKE: f32 = 0.3;
+// Longest cell, in characters, that a column is widened for
+const MAX_COLUMN_CHARS: usize = 40;
+
+// One PDF with the event header, `title` under it and the table split across as many pages as needed
+pub fn write_table_pdf(
+    path: &Path,
+    title: &str,
+    headers: &[String],
+    rows: &[Vec<String>],
+    header: &PrintHeader,
+    date_format: &str,
+) -> Result<(), Box<dyn Error>> {
+    let (doc, first_page, first_layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Table");
+    // The built-in PDF fonts only cover WinAnsi; embedding DejaVu Sans keeps names in other
+    // scripts and symbols outside Latin-1 intact
+    let font = doc.add_external_font(FALLBACK_FONT)?;
+    // A missing or unreadable logo shouldn't stop the export
+    let logo = header.logo_path.as_ref()
+        .and_then(|path| image_crate::open(path).ok())
+        .map(|image| DynamicImage::ImageRgb8(image.to_rgb8()));
+    
+    let widths = column_widths(headers, rows);
+    let table_top = PAGE_HEIGHT - MARGIN - LOGO_HEIGHT - 2.0 * ROW_HEIGHT;
+    let footer_space = if header.footer_text.trim().is_empty() { ROW_HEIGHT } else { 2.0 * ROW_HEIGHT };
+    let rows_per_page = (((table_top - MARGIN - footer_space) / ROW_HEIGHT) as usize).saturating_sub(1).max(1);
+    let page_count = rows.len().div_ceil(rows_per_page).max(1);
+    
+    for page in 0..page_count {
+        let layer = if page == 0 {
+            doc.get_page(first_page).get_layer(first_layer)
+        } else {
+            let (page, layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Table");
+            doc.get_page(page).get_layer(layer)
+        };
+        
+        draw_page_header(&layer, &font, logo.as_ref(), title, header, date_format);
+        
+        let mut y = table_top;
+        draw_row(&layer, &font, true, headers, &widths, y);
+        rule(&layer, y - 1.5);
+        for row in rows.iter().skip(page * rows_per_page).take(rows_per_page) {
+            y -= ROW_HEIGHT;
+            draw_row(&layer, &font, false, row, &widths, y);
+        }
+        
+        let mut footer_y = MARGIN;
+        layer.use_text(format!("Page {} of {}", page + 1, page_count), CELL_SIZE, Mm(PAGE_WIDTH - MARGIN - 25.0), Mm(footer_y), &font);
+        if !header.footer_text.trim().is_empty() {
+            footer_y += ROW_HEIGHT;
+            layer.use_text(header.footer_text.trim(), CELL_SIZE, Mm(MARGIN), Mm(footer_y), &font);
+        }
+    }
+    
+    doc.save(&mut BufWriter::new(File::create(path)?))?;
+    Ok(())
+}
+
+// Logo on the left, then the event name over the date and the table title
+fn draw_page_header(
+    layer: &PdfLayerReference,
+    font: &IndirectFontRef,
+    logo: Option<&DynamicImage>,
+    title: &str,
+    header: &PrintHeader,
+    date_format: &str,
+) {
+    let top = PAGE_HEIGHT - MARGIN;
+    let mut text_x = MARGIN;
+    if let Some(logo) = logo {
+        // Pick the DPI that makes the logo exactly LOGO_HEIGHT tall
+        let dpi = logo.height() as f32 * 25.4 / LOGO_HEIGHT;
+        Image::from_dynamic_image(logo).add_to_layer(layer.clone(), ImageTransform {
+            translate_x: Some(Mm(MARGIN)),
+            translate_y: Some(Mm(top - LOGO_HEIGHT)),
+            dpi: Some(dpi),
+            ..Default::default()
+        });
+        text_x += logo.width() as f32 * 25.4 / dpi + 5.0;
+    }
+    
+    let event_name = header.event_name.trim();
+    let heading = if event_name.is_empty() { title } else { event_name };
+    use_bold_text(layer, heading, TITLE_SIZE, Mm(text_x), Mm(top - TITLE_SIZE * MM_PER_POINT), font);
+    let subtitle = if event_name.is_empty() {
+        header.formatted_date(date_format)
+    } else {
+        format!("{} — {}", header.formatted_date(date_format), title)
+    };
+    layer.use_text(subtitle, SUBTITLE_SIZE, Mm(text_x), Mm(top - LOGO_HEIGHT + 1.0), font);
+}
+
+fn draw_row(layer: &PdfLayerReference, font: &IndirectFontRef, bold: bool, cells: &[String], widths: &[f32], y: f32) {
+    let mut x = MARGIN;
+    for (col, width) in widths.iter().enumerate() {
+        let cell = cells.get(col).map_or("", |cell| cell.trim());
+        let fits = (width / (CELL_SIZE * MM_PER_POINT * CHAR_WIDTH)) as usize;
+        let text = truncate(cell, fits.saturating_sub(1));
+        if bold {
+            use_bold_text(layer, &text, CELL_SIZE, Mm(x), Mm(y), font);
+        } else {
+            layer.use_text(text, CELL_SIZE, Mm(x), Mm(y), font);
+        }
+        x += width;
+    }
+}
+
+// Only the regular DejaVu Sans face is bundled, so bold text is the same glyphs with their outline stroked too
+fn use_bold_text(layer: &PdfLayerReference, text: &str, size: f32, x: Mm, y: Mm, font: &IndirectFontRef) {
+    layer.set_text_rendering_mode(TextRenderingMode::FillStroke);
+    layer.set_outline_thickness(BOLD_STROKE);
+    layer.use_text(text, size, x, y, font);
+    layer.set_text_rendering_mode(TextRenderingMode::Fill);
+}
+
+fn rule(layer: &PdfLayerReference, y: f32) {
+    layer.set_outline_thickness(0.5);
+    layer.add_line(Line {
+        points: vec![(Point::new(Mm(MARGIN), Mm(y)), false), (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(y)), false)],
+        is_closed: false,
+    });
+}
+
+// Share the printable width out in proportion to each column's longest value
+fn column_widths(headers: &[String], rows: &[Vec<String>]) -> Vec<f32> {
+    let chars: Vec<usize> = (0..headers.len())
+        .map(|col| {
+            std::iter::once(&headers[col])
+                .chain(rows.iter().filter_map(|row| row.get(col)))
+                .map(|cell| cell.trim().chars().count())
+                .max()
+                .unwrap_or(0)
+                .clamp(2, MAX_COLUMN_CHARS)
+        })
+        .collect();
+    let total = chars.iter().sum::<usize>().max(1) as f32;
+    let available = PAGE_WIDTH - 2.0 * MARGIN;
+    chars.iter().map(|&count| available * count as f32 / total).collect()
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut shortened: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    shortened.push('…');
+    shortened
+}
@@ -0,0 +1,82 @@
+use eframe::egui::Color32;
+use crate::settings::PrintHeader;
+
+// Colors and sizing copied from the on-screen table
+pub struct TableStyle {
+    pub font_size: f32,
+    pub text: Color32,
+    pub background: Color32,
+    pub stripe: Color32,
+}
+
+// HTML table for pasting into word processors: bold headers and alternating row backgrounds
+pub fn table_html(headers: &[String], rows: &[Vec<String>], style: &TableStyle) -> String {
+    let mut html = format!(
+        "<table style=\"border-collapse: collapse; font-size: {:.1}px; color: {};\">\n<tr>",
+        style.font_size, css_color(style.text)
+    );
+    for header in headers {
+        html.push_str(&format!("<th style=\"padding: 2px 6px; text-align: left;\"><b>{}</b></th>", escape_html(header)));
+    }
+    html.push_str("</tr>\n");
+    
+    for (index, row) in rows.iter().enumerate() {
+        // Odd rows get the stripe, like the striped grid on screen
+        let background = if index % 2 == 1 { style.stripe } else { style.background };
+        html.push_str(&format!("<tr style=\"background-color: {};\">", css_color(background)));
+        for col in 0..headers.len() {
+            let cell = row.get(col).map_or("", String::as_str);
+            html.push_str(&format!("<td style=\"padding: 2px 6px;\">{}</td>", escape_html(cell)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>");
+    html
+}
+
+// Logo, event name and date above the table; empty until an event name or logo is set
+pub fn print_header_html(header: &PrintHeader, date_format: &str) -> String {
+    if header.event_name.trim().is_empty() && header.logo_path.is_none() {
+        return String::new();
+    }
+    let mut html = String::from("<div style=\"margin-bottom: 8px;\">");
+    if let Some(logo) = &header.logo_path {
+        html.push_str(&format!(
+            "<img src=\"file://{}\" alt=\"\" style=\"max-height: 64px; vertical-align: middle; margin-right: 12px;\">",
+            escape_html(&logo.display().to_string())
+        ));
+    }
+    let date = header.formatted_date(date_format);
+    html.push_str(&format!(
+        "<span style=\"font-size: 1.4em;\"><b>{}</b></span> <span>{}</span></div>\n",
+        escape_html(&header.event_name),
+        escape_html(&date)
+    ));
+    html
+}
+
+pub fn print_footer_html(header: &PrintHeader) -> String {
+    if header.footer_text.trim().is_empty() {
+        return String::new();
+    }
+    format!("\n<p style=\"margin-top: 8px; font-size: 0.9em;\">{}</p>", escape_html(&header.footer_text))
+}
+
+// Tab-separated copy for targets that only take plain text
+pub fn table_plain_text(headers: &[String], rows: &[Vec<String>]) -> String {
+    std::iter::once(headers.join("\t"))
+        .chain(rows.iter().map(|row| row.join("\t")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn css_color(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write;
+use std::path::PathBuf;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use crate::data_types::DataSource;
+use crate::translate::DEFAULT_LIBRETRANSLATE_URL;
+
+pub const CONFIG_DIR: &str = "score_viewer";
+const SETTINGS_FILE: &str = "settings.toml";
+
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 1;
+pub const MAX_UPDATE_INTERVAL_SECS: u64 = 3600;
+const MAX_RECENT_FILES: usize = 10;
+
+// Preferences that survive restarts, stored as TOML in the OS config directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]  // Missing keys fall back to defaults, so older files keep loading
+pub struct Settings {
+    pub update_interval_secs: u64,
+    pub comment_file: String,  // Shared JSON file for the comment feed, usually on a network drive
+    pub comment_username: String,
+    pub recent_files: Vec<PathBuf>,  // Most recently opened first
+    pub libretranslate_url: String,  // Translate endpoint, for self-hosted instances
+    pub column_configs: HashMap<String, ColumnConfig>,  // By data source cache key
+    pub dnx_patterns: Vec<String>,  // Result codes that strike a row through, matched case-insensitively
+    pub date_display_format: String,  // chrono format string
+    pub print_header: PrintHeader,
+    pub strict_schema: bool,  // Reject loads whose columns don't match `expected_columns`
+    pub expected_columns: Vec<String>,
+    // Session state, written when the window closes
+    pub window_size: Option<[f32; 2]>,
+    pub window_position: Option<[f32; 2]>,
+    pub dark_theme: bool,
+    pub last_source: Option<SavedSource>,
+}
+
+// The data sources that can be reopened on the next launch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SavedSource {
+    Local(PathBuf),
+    Workbook(PathBuf, usize),
+    Cloud { url: String, sheet: String },
+    Url(String),
+}
+
+impl SavedSource {
+    // None for standard input and sources that only exist in memory
+    pub fn from_source(source: &DataSource) -> Option<SavedSource> {
+        match source {
+            DataSource::Local(path) if path.as_os_str() == "-" => None,
+            DataSource::Local(path) => Some(SavedSource::Local(path.clone())),
+            DataSource::Workbook(path, sheet_index) => Some(SavedSource::Workbook(path.clone(), *sheet_index)),
+            DataSource::Cloud(url, sheet) => Some(SavedSource::Cloud { url: url.clone(), sheet: sheet.clone() }),
+            DataSource::Url(url) => Some(SavedSource::Url(url.clone())),
+            DataSource::Inline(_) | DataSource::Merged { .. } => None,
+        }
+    }
+    
+    pub fn to_source(&self) -> DataSource {
+        match self {
+            SavedSource::Local(path) => DataSource::Local(path.clone()),
+            SavedSource::Workbook(path, sheet_index) => DataSource::Workbook(path.clone(), *sheet_index),
+            SavedSource::Cloud { url, sheet } => DataSource::Cloud(url.clone(), sheet.clone()),
+            SavedSource::Url(url) => DataSource::Url(url.clone()),
+        }
+    }
+}
+
+// Event details put above and below exported tables
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrintHeader {
+    pub event_name: String,
+    pub event_date: NaiveDate,
+    pub logo_path: Option<PathBuf>,
+    pub footer_text: String,
+}
+
+impl Default for PrintHeader {
+    fn default() -> Self {
+        PrintHeader {
+            event_name: String::new(),
+            event_date: chrono::Local::now().date_naive(),
+            logo_path: None,
+            footer_text: String::new(),
+        }
+    }
+}
+
+impl PrintHeader {
+    // The event date in a chrono format; an invalid format falls back to ISO rather than panicking
+    pub fn formatted_date(&self, format: &str) -> String {
+        let mut date = String::new();
+        if write!(date, "{}", self.event_date.format(format)).is_err() {
+            date = self.event_date.to_string();
+        }
+        date
+    }
+}
+
+// Column layout remembered for one data source, indexed like the columns as loaded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnConfig {
+    pub visibility: Vec<bool>,
+    pub order: Vec<usize>,  // Loaded column shown at each position
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            update_interval_secs: 5,
+            comment_file: String::new(),
+            comment_username: String::new(),
+            recent_files: Vec::new(),
+            libretranslate_url: DEFAULT_LIBRETRANSLATE_URL.to_string(),
+            column_configs: HashMap::new(),
+            dnx_patterns: ["dns", "dnf", "dq"].map(String::from).to_vec(),
+            date_display_format: "%Y-%m-%d".to_string(),
+            print_header: PrintHeader::default(),
+            strict_schema: false,
+            expected_columns: Vec::new(),
+            window_size: None,
+            window_position: None,
+            dark_theme: true,
+            last_source: None,
+        }
+    }
+}
+
+impl Settings {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(CONFIG_DIR).join(SETTINGS_FILE))
+    }
+    
+    // Defaults when there is no settings file yet or it can't be read
+    pub fn load() -> Settings {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+    
+    // Move `path` to the top of the recent files, dropping the oldest beyond the limit
+    pub fn add_recent_file(&mut self, path: PathBuf) {
+        self.recent_files.retain(|recent| *recent != path);
+        self.recent_files.insert(0, path);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+    
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = Self::path().ok_or("No config directory on this system")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
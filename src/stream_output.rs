@@ -0,0 +1,113 @@
+use std::io::Write;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, SyncSender};
+use std::thread;
+use eframe::egui::ColorImage;
+
+pub const STREAM_FPS: u32 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputResolution {
+    Hd720,
+    Hd1080,
+}
+
+impl OutputResolution {
+    pub const ALL: [OutputResolution; 2] = [OutputResolution::Hd720, OutputResolution::Hd1080];
+    
+    pub fn size(self) -> (usize, usize) {
+        match self {
+            OutputResolution::Hd720 => (1280, 720),
+            OutputResolution::Hd1080 => (1920, 1080),
+        }
+    }
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            OutputResolution::Hd720 => "720p",
+            OutputResolution::Hd1080 => "1080p",
+        }
+    }
+}
+
+// Scoreboard video feed sent over NDI by an ffmpeg child process built with libndi_newtek
+pub struct StreamOutput {
+    child: Child,
+    frames: SyncSender<Vec<u8>>,
+    resolution: OutputResolution,
+}
+
+impl StreamOutput {
+    pub fn start(source_name: &str, resolution: OutputResolution) -> std::io::Result<StreamOutput> {
+        let (width, height) = resolution.size();
+        let mut child = Command::new("ffmpeg")
+            .args(["-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", width, height), "-r", &STREAM_FPS.to_string(), "-i", "-"])
+            .args(["-f", "libndi_newtek", source_name])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().ok_or_else(|| std::io::Error::other("ffmpeg has no input pipe"))?;
+        
+        // Writing a frame can block on ffmpeg, so it happens off the UI thread.
+        // A single slot means a slow encoder drops frames instead of queueing them.
+        let (frames, receiver) = mpsc::sync_channel(1);
+        thread::spawn(move || write_frames(stdin, receiver));
+        Ok(StreamOutput { child, frames, resolution })
+    }
+    
+    // Scale the captured window to the output size and hand it to the encoder
+    pub fn push_frame(&self, image: &ColorImage) {
+        let (width, height) = self.resolution.size();
+        let _ = self.frames.try_send(scale_rgba(image, width, height));
+    }
+    
+    // False once ffmpeg has exited, e.g. because it was built without NDI support
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+    
+    pub fn resolution(&self) -> OutputResolution {
+        self.resolution
+    }
+}
+
+impl Drop for StreamOutput {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn write_frames(mut stdin: ChildStdin, frames: mpsc::Receiver<Vec<u8>>) {
+    for frame in frames {
+        if stdin.write_all(&frame).is_err() {
+            break;
+        }
+    }
+}
+
+// Nearest-neighbor resize, letterboxed to keep the window's aspect ratio
+fn scale_rgba(image: &ColorImage, width: usize, height: usize) -> Vec<u8> {
+    let mut rgba = [0, 0, 0, 255].repeat(width * height);
+    let [source_width, source_height] = image.size;
+    if source_width == 0 || source_height == 0 {
+        return rgba;
+    }
+    let scale = (width as f32 / source_width as f32).min(height as f32 / source_height as f32);
+    let scaled_width = ((source_width as f32 * scale) as usize).min(width);
+    let scaled_height = ((source_height as f32 * scale) as usize).min(height);
+    let left = (width - scaled_width) / 2;
+    let top = (height - scaled_height) / 2;
+    
+    for y in 0..scaled_height {
+        let source_y = ((y as f32 / scale) as usize).min(source_height - 1);
+        for x in 0..scaled_width {
+            let source_x = ((x as f32 / scale) as usize).min(source_width - 1);
+            let pixel = image.pixels[source_y * source_width + source_x].to_array();
+            let offset = ((top + y) * width + left + x) * 4;
+            rgba[offset..offset + 4].copy_from_slice(&pixel);
+        }
+    }
+    rgba
+}
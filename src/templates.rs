@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use crate::data_types::HeaderConfig;
+
+// Header rules for one kind of competition, plus the raw header fragments that identify it
+pub struct SportTemplate {
+    pub name: &'static str,
+    pub patterns: &'static [&'static str],
+    pub hidden_columns: &'static [&'static str],
+    pub numbered_prefixes: &'static [(&'static str, &'static str)],
+    pub replacements: &'static [(&'static str, &'static str)],
+}
+
+const COMMON_HIDDEN_COLUMNS: &[&str] = &[
+    "sport_id", "team_members", "team_name",
+    "info", "result_code", "position_pre"
+];
+
+pub const SPORT_TEMPLATES: &[SportTemplate] = &[
+    SportTemplate {
+        name: "ISSF Shooting",
+        patterns: &["napat", "part-", "psum-"],
+        hidden_columns: COMMON_HIDDEN_COLUMNS,
+        numbered_prefixes: &[("part-", "S"), ("psum-", "P")],
+        replacements: &[
+            ("category", "Series"),
+            ("first_name", "Name"),
+            ("last_name", "Surname"),
+            ("organization", "Club"),
+            ("napat", "X"),
+            ("result", "Result"),
+            ("posit.", "Rank")
+        ],
+    },
+    SportTemplate {
+        name: "Archery",
+        patterns: &["arrow", "10x"],
+        hidden_columns: COMMON_HIDDEN_COLUMNS,
+        numbered_prefixes: &[("end-", "E"), ("arrow-", "A")],
+        replacements: &[
+            ("category", "Class"),
+            ("first_name", "Name"),
+            ("last_name", "Surname"),
+            ("organization", "Club"),
+            ("10x", "10+X"),
+            ("result", "Result"),
+            ("posit.", "Rank")
+        ],
+    },
+    SportTemplate {
+        name: "Athletics",
+        patterns: &["time", "distance"],
+        hidden_columns: COMMON_HIDDEN_COLUMNS,
+        numbered_prefixes: &[("attempt-", "A")],
+        replacements: &[
+            ("category", "Event"),
+            ("first_name", "Name"),
+            ("last_name", "Surname"),
+            ("organization", "Club"),
+            ("time", "Time"),
+            ("distance", "Distance"),
+            ("result", "Result"),
+            ("posit.", "Rank")
+        ],
+    },
+];
+
+impl SportTemplate {
+    pub fn header_config(&self) -> HeaderConfig {
+        HeaderConfig {
+            hidden_columns: self.hidden_columns.iter().map(|col| col.to_string()).collect(),
+            numbered_prefixes: self.numbered_prefixes.iter()
+                .map(|(pattern, prefix)| (pattern.to_string(), prefix.to_string()))
+                .collect(),
+            replacements: self.replacements.iter()
+                .map(|(original, replacement)| (original.to_string(), replacement.to_string()))
+                .collect(),
+            column_overrides: HashMap::new(),
+        }
+    }
+}
+
+impl Default for HeaderConfig {
+    fn default() -> Self {
+        SPORT_TEMPLATES[0].header_config()
+    }
+}
+
+// Pick the template whose patterns match the most raw headers
+pub fn detect_template(raw_headers: &[String]) -> Option<usize> {
+    let lowercase: Vec<String> = raw_headers.iter().map(|header| header.to_lowercase()).collect();
+    
+    let mut best: Option<(usize, usize)> = None;
+    for (index, template) in SPORT_TEMPLATES.iter().enumerate() {
+        let score = template.patterns.iter()
+            .filter(|pattern| lowercase.iter().any(|header| header.contains(*pattern)))
+            .count();
+        if score > best.map_or(0, |(_, best_score)| best_score) {
+            best = Some((index, score));
+        }
+    }
+    best.map(|(index, _)| index)
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use eframe::egui::{Color32, Stroke, Visuals};
+use serde::{Deserialize, Serialize};
+use crate::settings::CONFIG_DIR;
+
+const THEME_DIR: &str = "themes";
+pub const THEME_EXTENSION: &str = "theme.toml";
+
+// Saved themes live next to the settings file rather than wherever the viewer was started
+pub fn theme_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CONFIG_DIR).join(THEME_DIR))
+}
+
+type ColorAccessor = fn(&mut Visuals) -> &mut Color32;
+// (key in the theme file, label, field)
+type ColorField = (&'static str, &'static str, ColorAccessor);
+
+// Editable colors grouped by category
+pub const THEME_COLORS: &[(&str, &[ColorField])] = &[
+    ("Background", &[
+        ("window_fill", "Window fill", |v| &mut v.window_fill),
+        ("panel_fill", "Panel fill", |v| &mut v.panel_fill),
+        ("faint_bg", "Faint background (stripes)", |v| &mut v.faint_bg_color),
+        ("extreme_bg", "Extreme background (inputs)", |v| &mut v.extreme_bg_color),
+        ("code_bg", "Code background", |v| &mut v.code_bg_color),
+    ]),
+    ("Text", &[
+        ("text", "Text", |v| &mut v.widgets.noninteractive.fg_stroke.color),
+        ("hyperlink", "Hyperlinks", |v| &mut v.hyperlink_color),
+        ("warning", "Warnings", |v| &mut v.warn_fg_color),
+        ("error", "Errors", |v| &mut v.error_fg_color),
+    ]),
+    ("Selection", &[
+        ("selection_bg", "Selection background", |v| &mut v.selection.bg_fill),
+        ("selection_stroke", "Selection outline", |v| &mut v.selection.stroke.color),
+    ]),
+    ("Widgets", &[
+        ("widget_fill", "Widget fill", |v| &mut v.widgets.inactive.weak_bg_fill),
+        ("widget_text", "Widget text", |v| &mut v.widgets.inactive.fg_stroke.color),
+        ("hovered_fill", "Hovered fill", |v| &mut v.widgets.hovered.weak_bg_fill),
+        ("hovered_text", "Hovered text", |v| &mut v.widgets.hovered.fg_stroke.color),
+        ("active_fill", "Active fill", |v| &mut v.widgets.active.weak_bg_fill),
+        ("active_text", "Active text", |v| &mut v.widgets.active.fg_stroke.color),
+        ("separator", "Separators and borders", |v| &mut v.widgets.noninteractive.bg_stroke.color),
+    ]),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemePreset {
+    pub dark_mode: bool,
+    pub colors: BTreeMap<String, [u8; 4]>,
+}
+
+impl ThemePreset {
+    pub fn from_visuals(visuals: &Visuals) -> Self {
+        let mut visuals = visuals.clone();
+        let mut colors = BTreeMap::new();
+        for (_, fields) in THEME_COLORS {
+            for (key, _, field) in fields.iter() {
+                colors.insert(key.to_string(), field(&mut visuals).to_array());
+            }
+        }
+        ThemePreset {
+            dark_mode: visuals.dark_mode,
+            colors,
+        }
+    }
+    
+    // Start from the matching built-in theme so missing keys fall back to sensible colors
+    pub fn to_visuals(&self) -> Visuals {
+        let mut visuals = if self.dark_mode { Visuals::dark() } else { Visuals::light() };
+        for (_, fields) in THEME_COLORS {
+            for (key, _, field) in fields.iter() {
+                if let Some([r, g, b, a]) = self.colors.get(*key) {
+                    *field(&mut visuals) = Color32::from_rgba_premultiplied(*r, *g, *b, *a);
+                }
+            }
+        }
+        visuals
+    }
+}
+
+// Black background, white text and thick focus outlines for accessibility mode
+pub fn high_contrast_visuals() -> Visuals {
+    let mut visuals = Visuals::dark();
+    visuals.override_text_color = Some(Color32::WHITE);
+    visuals.panel_fill = Color32::BLACK;
+    visuals.window_fill = Color32::BLACK;
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.faint_bg_color = Color32::from_gray(35);
+    visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+    visuals.selection.bg_fill = Color32::from_rgb(0, 0, 160);
+    visuals.selection.stroke = Stroke::new(3.0, Color32::YELLOW);
+    
+    let widgets = &mut visuals.widgets;
+    for widget in [&mut widgets.noninteractive, &mut widgets.inactive, &mut widgets.open] {
+        widget.fg_stroke.color = Color32::WHITE;
+        widget.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+    }
+    widgets.inactive.bg_fill = Color32::BLACK;
+    widgets.inactive.weak_bg_fill = Color32::BLACK;
+    // Hovered visuals double as the keyboard focus outline
+    widgets.hovered.fg_stroke.color = Color32::YELLOW;
+    widgets.hovered.bg_stroke = Stroke::new(3.0, Color32::YELLOW);
+    widgets.active.fg_stroke.color = Color32::BLACK;
+    widgets.active.bg_fill = Color32::YELLOW;
+    widgets.active.weak_bg_fill = Color32::YELLOW;
+    widgets.active.bg_stroke = Stroke::new(3.0, Color32::WHITE);
+    visuals
+}
+
+pub fn save_theme<P: AsRef<Path>>(path: P, visuals: &Visuals) -> Result<(), Box<dyn Error>> {
+    let content = toml::to_string_pretty(&ThemePreset::from_visuals(visuals))?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+pub fn load_theme<P: AsRef<Path>>(path: P) -> Result<Visuals, Box<dyn Error>> {
+    let content = std::fs::read_to_string(path)?;
+    let preset: ThemePreset = toml::from_str(&content)?;
+    Ok(preset.to_visuals())
+}
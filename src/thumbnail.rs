@@ -0,0 +1,55 @@
+use eframe::IconData;
+use crate::data_types::TableData;
+
+const ICON_SIZE: usize = 64;
+const MAX_COLUMNS: usize = 10;
+const MAX_ROWS: usize = 20;
+
+const BACKGROUND: [u8; 4] = [30, 30, 30, 255];
+const HEADER: [u8; 4] = [240, 240, 240, 255];
+const NUMBER_CELL: [u8; 4] = [70, 130, 200, 255];
+const TEXT_CELL: [u8; 4] = [150, 150, 150, 255];
+const EMPTY_CELL: [u8; 4] = [55, 55, 55, 255];
+
+// Miniature of the top-left corner of the table for the window icon: a white header band
+// over one colored block per cell (blue for numbers, gray for text)
+pub fn table_thumbnail(data: &TableData) -> IconData {
+    let mut rgba = BACKGROUND.repeat(ICON_SIZE * ICON_SIZE);
+    let columns = data.headers.len().clamp(1, MAX_COLUMNS);
+    let rows = data.rows.len().min(MAX_ROWS);
+    let cell_width = ICON_SIZE / columns;
+    let cell_height = ICON_SIZE / (rows + 1);
+    
+    let mut fill = |col: usize, row: usize, color: [u8; 4]| {
+        // Leave a one-pixel gap between cells so the grid stays readable
+        for y in row * cell_height..(row + 1) * cell_height - 1 {
+            for x in col * cell_width..(col + 1) * cell_width - 1 {
+                let offset = (y * ICON_SIZE + x) * 4;
+                rgba[offset..offset + 4].copy_from_slice(&color);
+            }
+        }
+    };
+    
+    for col in 0..columns {
+        fill(col, 0, HEADER);
+    }
+    for (row_idx, row) in data.rows.iter().take(rows).enumerate() {
+        for col in 0..columns {
+            let cell = row.get(col).map_or("", |cell| cell.trim());
+            let color = if cell.is_empty() {
+                EMPTY_CELL
+            } else if cell.parse::<f64>().is_ok() {
+                NUMBER_CELL
+            } else {
+                TEXT_CELL
+            };
+            fill(col, row_idx + 1, color);
+        }
+    }
+    
+    IconData {
+        rgba,
+        width: ICON_SIZE as u32,
+        height: ICON_SIZE as u32,
+    }
+}
@@ -0,0 +1,30 @@
+use std::error::Error;
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+
+pub const DEFAULT_LIBRETRANSLATE_URL: &str = "https://libretranslate.com/translate";
+
+// (header, English translation) for each header the API changed, in the order given
+pub fn translate_headers(api_url: &str, headers: &[String]) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let client = Client::new();
+    let mut translations = Vec::new();
+    for header in headers {
+        let response: Value = client.post(api_url)
+            .json(&json!({
+                "q": header,
+                "source": "auto",
+                "target": "en",
+                "format": "text",
+            }))
+            .send()?
+            .error_for_status()?
+            .json()?;
+        let translated = response["translatedText"].as_str()
+            .ok_or("Unexpected response from the translation service")?
+            .trim();
+        if !translated.is_empty() && !translated.eq_ignore_ascii_case(header) {
+            translations.push((header.clone(), translated.to_string()));
+        }
+    }
+    Ok(translations)
+}
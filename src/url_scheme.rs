@@ -0,0 +1,281 @@
+use std::error::Error;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+use eframe::egui;
+use crate::app_log;
+use crate::data_handler::is_xlsx_path;
+use crate::data_types::DataSource;
+use crate::settings::CONFIG_DIR;
+
+pub const URL_SCHEME: &str = "simpleviewer";
+// Whether register_url_scheme can set up a handler that actually receives links here
+pub const LINKS_SUPPORTED: bool = !cfg!(target_os = "macos");
+
+// Port and token of the running instance, readable only by the user who started it
+const INSTANCE_FILE: &str = "instance";
+const HANDOVER_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_HANDOVER_BYTES: u64 = 64 * 1024;
+const ACCEPTED: &str = "ok";
+
+// simpleviewer://open?url=<encoded url>[&sheet=<encoded name>]
+// A Google Sheets document, or any link naming a sheet, opens through the Sheets API; other web
+// addresses are read as CSV like File > Open URL, and anything else as a local file
+pub fn parse_link(link: &str) -> Option<DataSource> {
+    let query = link.strip_prefix(URL_SCHEME)?
+        .strip_prefix("://open")?
+        .trim_start_matches('/')
+        .strip_prefix('?')?;
+    
+    let mut url = None;
+    let mut sheet = None;
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = urlencoding::decode(&value.replace('+', " ")).ok()?.into_owned();
+        match key {
+            "url" => url = Some(value),
+            "sheet" => sheet = Some(value),
+            _ => {}
+        }
+    }
+    
+    let url = url?;
+    if sheet.is_some() || url.contains("docs.google.com/spreadsheets/d/") {
+        Some(DataSource::Cloud(url, sheet.unwrap_or_default()))
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+        Some(DataSource::Url(url))
+    } else {
+        let path = PathBuf::from(url.strip_prefix("file://").unwrap_or(&url));
+        if is_xlsx_path(&path) {
+            Some(DataSource::Workbook(path, 0))
+        } else {
+            Some(DataSource::Local(path))
+        }
+    }
+}
+
+// The running instance's listener; later launches hand their link to it instead of opening a window.
+// A link is only accepted together with the token from the instance file, so other users'
+// processes can't push sources into this viewer.
+pub struct InstanceListener {
+    listener: TcpListener,
+    token: String,
+}
+
+fn instance_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CONFIG_DIR).join(INSTANCE_FILE))
+}
+
+fn read_instance_file() -> Option<(u16, String)> {
+    let content = std::fs::read_to_string(instance_file()?).ok()?;
+    let (port, token) = content.split_once('\n')?;
+    Some((port.parse().ok()?, token.trim().to_string()))
+}
+
+fn write_instance_file(port: u16, token: &str) -> Result<(), Box<dyn Error>> {
+    let path = instance_file().ok_or("No config directory on this system")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // A file left by an older version may have been created with wider permissions
+        if path.exists() {
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(&path)?.write_all(format!("{}\n{}\n", port, token).as_bytes())?;
+    Ok(())
+}
+
+// Deliver `message` (an empty one just checks the instance is alive); true once the instance accepts it
+fn send_to_running_instance(message: &str) -> bool {
+    let Some((port, token)) = read_instance_file() else {
+        return false;
+    };
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let Ok(mut stream) = TcpStream::connect_timeout(&address, HANDOVER_TIMEOUT) else {
+        return false;
+    };
+    let mut reply = String::new();
+    // Whatever else may have taken the port since won't answer with ACCEPTED
+    stream.set_read_timeout(Some(HANDOVER_TIMEOUT)).is_ok()
+        && stream.write_all(format!("{}\n{}", token, message).as_bytes()).is_ok()
+        && stream.shutdown(Shutdown::Write).is_ok()
+        && stream.take(MAX_HANDOVER_BYTES).read_to_string(&mut reply).is_ok()
+        && reply == ACCEPTED
+}
+
+// Become the single instance on a random local port, or return None if another viewer is running
+pub fn claim_instance() -> Option<InstanceListener> {
+    if send_to_running_instance("") {
+        return None;
+    }
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).ok()?;
+    let token = format!("{:032x}", rand::random::<u128>());
+    if let Err(err) = write_instance_file(listener.local_addr().ok()?.port(), &token) {
+        app_log::log(format!("Could not write instance file: {}", err));
+        return None;
+    }
+    Some(InstanceListener { listener, token })
+}
+
+// Send a link to the running instance; false if there is none
+pub fn forward_to_running_instance(link: &str) -> bool {
+    !link.is_empty() && send_to_running_instance(link)
+}
+
+// Accept links handed over by later launches on a background thread, waking the UI for each one
+pub fn listen_for_links(instance: InstanceListener, ctx: egui::Context) -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in instance.listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let mut message = String::new();
+            // The timeout only stops a stuck sender from holding up the links after it
+            let ready = stream.set_read_timeout(Some(HANDOVER_TIMEOUT)).is_ok();
+            if !ready || (&mut stream).take(MAX_HANDOVER_BYTES).read_to_string(&mut message).is_err() {
+                continue;
+            }
+            let Some((token, link)) = message.split_once('\n') else {
+                continue;
+            };
+            if token != instance.token {
+                app_log::log("Ignoring a link handed over without the instance token".to_string());
+                continue;
+            }
+            let _ = stream.write_all(ACCEPTED.as_bytes());
+            let link = link.trim();
+            if link.is_empty() {
+                continue;
+            }
+            if sender.send(link.to_string()).is_err() {
+                break;
+            }
+            ctx.request_repaint();
+        }
+    });
+    receiver
+}
+
+#[cfg(windows)]
+pub fn register_url_scheme() -> Result<(), Box<dyn Error>> {
+    use winreg::enums::HKEY_CURRENT_USER;
+    use winreg::RegKey;
+    
+    let exe = std::env::current_exe()?;
+    let classes = RegKey::predef(HKEY_CURRENT_USER).open_subkey("Software\\Classes")?;
+    let (scheme, _) = classes.create_subkey(URL_SCHEME)?;
+    scheme.set_value("", &"URL:Score Viewer")?;
+    scheme.set_value("URL Protocol", &"")?;
+    let (command, _) = scheme.create_subkey("shell\\open\\command")?;
+    command.set_value("", &format!("\"{}\" \"%1\"", exe.display()))?;
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+pub fn register_url_scheme() -> Result<(), Box<dyn Error>> {
+    // macOS delivers opened links as an Apple Event (kAEGetURL) rather than in argv, and eframe
+    // 0.22 gives no access to those events, so a registered link would start the app and be lost
+    Err(format!("{}:// links are not supported on macOS", URL_SCHEME).into())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn register_url_scheme() -> Result<(), Box<dyn Error>> {
+    let exe = std::env::current_exe()?;
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or("Could not determine the applications directory")?;
+    let applications = data_home.join("applications");
+    std::fs::create_dir_all(&applications)?;
+    
+    let desktop_file = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Score Viewer\n\
+         Exec=\"{}\" %u\n\
+         Terminal=false\n\
+         NoDisplay=true\n\
+         MimeType=x-scheme-handler/{};\n",
+        exe.display(), URL_SCHEME
+    );
+    std::fs::write(applications.join("score-viewer.desktop"), desktop_file)?;
+    
+    let status = std::process::Command::new("xdg-mime")
+        .args(["default", "score-viewer.desktop", &format!("x-scheme-handler/{}", URL_SCHEME)])
+        .status()?;
+    if !status.success() {
+        return Err("xdg-mime failed to register the link handler".into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::*;
+    
+    #[test]
+    fn google_sheet_links_open_through_the_api() {
+        let link = "simpleviewer://open?url=https%3A%2F%2Fdocs.google.com%2Fspreadsheets%2Fd%2Fabc%2Fedit";
+        assert!(matches!(
+            parse_link(link),
+            Some(DataSource::Cloud(url, sheet)) if url == "https://docs.google.com/spreadsheets/d/abc/edit" && sheet.is_empty()
+        ));
+    }
+    
+    #[test]
+    fn sheet_parameter_selects_the_sheet() {
+        let link = "simpleviewer://open/?url=https%3A%2F%2Fexample.com%2Fscores&sheet=Men+U18";
+        assert!(matches!(
+            parse_link(link),
+            Some(DataSource::Cloud(url, sheet)) if url == "https://example.com/scores" && sheet == "Men U18"
+        ));
+    }
+    
+    #[test]
+    fn other_web_links_load_as_csv() {
+        assert!(matches!(
+            parse_link("simpleviewer://open?url=http%3A%2F%2Fexample.com%2Fresults.csv"),
+            Some(DataSource::Url(url)) if url == "http://example.com/results.csv"
+        ));
+    }
+    
+    #[test]
+    fn paths_load_as_local_files() {
+        assert!(matches!(
+            parse_link("simpleviewer://open?url=file%3A%2F%2F%2Ftmp%2Fresults.csv"),
+            Some(DataSource::Local(path)) if path == Path::new("/tmp/results.csv")
+        ));
+        assert!(matches!(
+            parse_link("simpleviewer://open?url=%2Ftmp%2Fresults.csv&unknown=1"),
+            Some(DataSource::Local(path)) if path == Path::new("/tmp/results.csv")
+        ));
+    }
+    
+    #[test]
+    fn workbooks_open_on_their_first_sheet() {
+        assert!(matches!(
+            parse_link("simpleviewer://open?url=%2Ftmp%2FResults.XLSX"),
+            Some(DataSource::Workbook(path, 0)) if path == Path::new("/tmp/Results.XLSX")
+        ));
+    }
+    
+    #[test]
+    fn rejects_malformed_links() {
+        assert!(parse_link("otherscheme://open?url=x").is_none());
+        assert!(parse_link("simpleviewer://delete?url=x").is_none());
+        assert!(parse_link("simpleviewer://open").is_none());
+        assert!(parse_link("simpleviewer://open?sheet=Results").is_none());
+        assert!(parse_link("simpleviewer://open?url=%FF%FE").is_none());
+    }
+}